@@ -0,0 +1,242 @@
+use crate::payload::element_key;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Statistic {
+    Min,
+    Max,
+    Mean,
+    P95,
+    Last,
+}
+
+impl Statistic {
+    pub const ALL: [Statistic; 5] = [
+        Statistic::Min,
+        Statistic::Max,
+        Statistic::Mean,
+        Statistic::P95,
+        Statistic::Last,
+    ];
+
+    fn name(&self) -> &'static str {
+        match *self {
+            Statistic::Min => "min",
+            Statistic::Max => "max",
+            Statistic::Mean => "mean",
+            Statistic::P95 => "p95",
+            Statistic::Last => "last",
+        }
+    }
+
+    // NOTE: 'values' are in sampling order, percentiles use the nearest-rank method
+    fn compute(&self, values: &[f64]) -> f64 {
+        match *self {
+            Statistic::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Statistic::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Statistic::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Statistic::P95 => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let rank = (0.95 * sorted.len() as f64).ceil() as usize;
+                sorted[rank.saturating_sub(1)]
+            }
+            Statistic::Last => values[values.len() - 1],
+        }
+    }
+}
+
+// NOTE: collects samples of a resource group during a window, and replaces
+//       every numeric value of the last sample with the statistics of that
+//       value over the window, attributes identifying array elements, like
+//       the CPU 'id', are kept as they are, samples where the value is 'null',
+//       like a network rate after a counter reset, are left out of the
+//       statistics, which are 'null' only when the value is never a number
+#[derive(Debug)]
+pub struct Aggregator {
+    statistics: Vec<Statistic>,
+    samples: Vec<Value>,
+}
+
+impl Aggregator {
+    pub fn new(statistics: &[Statistic]) -> Aggregator {
+        Aggregator {
+            statistics: statistics.to_vec(),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, sample: Value) {
+        self.samples.push(sample);
+    }
+
+    // NOTE: returns the aggregated window and starts a new one
+    pub fn aggregate(&mut self) -> Option<Value> {
+        let samples: Vec<Value> = self.samples.drain(..).collect();
+        let last = samples.last()?;
+        let samples: Vec<&Value> = samples.iter().collect();
+
+        Some(self.aggregate_value(last, &samples))
+    }
+
+    fn aggregate_value(&self, last: &Value, samples: &[&Value]) -> Value {
+        match last {
+            Value::Number(_) | Value::Null => {
+                let values: Vec<f64> = samples.iter().filter_map(|value| value.as_f64()).collect();
+                if values.is_empty() {
+                    return Value::Null;
+                }
+                let mut statistics = Map::new();
+                self.statistics.iter().for_each(|statistic| {
+                    statistics.insert(
+                        statistic.name().to_owned(),
+                        serde_json::json!(statistic.compute(&values)),
+                    );
+                });
+                Value::Object(statistics)
+            }
+            Value::Object(object) => {
+                let key_field = element_key(last).map(|(field, _)| field);
+                Value::Object(
+                    object
+                        .iter()
+                        .map(|(key, value)| match key_field {
+                            Some(field) if field == key => (key.to_owned(), value.clone()),
+                            _ => {
+                                let samples: Vec<&Value> = samples
+                                    .iter()
+                                    .filter_map(|sample| sample.get(key))
+                                    .collect();
+                                (key.to_owned(), self.aggregate_value(value, &samples))
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(array) => Value::Array(
+                array
+                    .iter()
+                    .enumerate()
+                    .map(|(i, element)| {
+                        let key = element_key(element);
+                        let samples: Vec<&Value> = samples
+                            .iter()
+                            .filter_map(|sample| match &key {
+                                Some(key) => sample.as_array().and_then(|elements| {
+                                    elements
+                                        .iter()
+                                        .find(|element| element_key(element).as_ref() == Some(key))
+                                }),
+                                None => sample.get(i),
+                            })
+                            .collect();
+                        self.aggregate_value(element, &samples)
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_statistics() {
+        let values = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0];
+
+        assert_eq!(Statistic::Min.compute(&values), 1.0);
+        assert_eq!(Statistic::Max.compute(&values), 9.0);
+        assert_eq!(Statistic::Mean.compute(&values), 3.9);
+        assert_eq!(Statistic::P95.compute(&values), 9.0);
+        assert_eq!(Statistic::Last.compute(&values), 3.0);
+        assert_eq!(Statistic::P95.compute(&[7.0]), 7.0);
+
+        let values: Vec<f64> = (1..=100).map(|value| value as f64).collect();
+        assert_eq!(Statistic::P95.compute(&values), 95.0);
+    }
+
+    #[test]
+    fn test_aggregate_window() {
+        let mut aggregator = Aggregator::new(&Statistic::ALL);
+
+        assert_eq!(aggregator.aggregate(), None);
+
+        aggregator.add(json!({"cpus": [{"id": 0, "load": 10.0}, {"id": 1, "load": 90.0}]}));
+        aggregator.add(json!({"cpus": [{"id": 0, "load": 30.0}, {"id": 1, "load": 70.0}]}));
+        aggregator.add(json!({"cpus": [{"id": 0, "load": 20.0}, {"id": 1, "load": 80.0}]}));
+
+        assert_eq!(
+            aggregator.aggregate(),
+            Some(json!({
+                "cpus": [
+                    {
+                        "id": 0,
+                        "load": {"min": 10.0, "max": 30.0, "mean": 20.0, "p95": 30.0, "last": 20.0}
+                    },
+                    {
+                        "id": 1,
+                        "load": {"min": 70.0, "max": 90.0, "mean": 80.0, "p95": 90.0, "last": 80.0}
+                    }
+                ]
+            }))
+        );
+        assert_eq!(aggregator.aggregate(), None);
+    }
+
+    #[test]
+    fn test_aggregate_by_element_key() {
+        let mut aggregator = Aggregator::new(&[Statistic::Max, Statistic::Last]);
+
+        aggregator.add(json!({"networks": [
+            {"interface": "eth0", "rx_bytes": 10},
+            {"interface": "eth1", "rx_bytes": 500}
+        ]}));
+        aggregator.add(json!({"networks": [
+            {"interface": "eth1", "rx_bytes": 600},
+            {"interface": "wlan0", "rx_bytes": 5}
+        ]}));
+
+        assert_eq!(
+            aggregator.aggregate(),
+            Some(json!({
+                "networks": [
+                    {"interface": "eth1", "rx_bytes": {"max": 600.0, "last": 600.0}},
+                    {"interface": "wlan0", "rx_bytes": {"max": 5.0, "last": 5.0}}
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_aggregate_null_values() {
+        let mut aggregator = Aggregator::new(&[Statistic::Max, Statistic::Last]);
+        let rate =
+            |rate: Value| json!({"networks": [{"interface": "eth0", "rx_bytes_per_s": rate}]});
+
+        aggregator.add(rate(json!(100.0)));
+        aggregator.add(rate(json!(300.0)));
+        aggregator.add(rate(Value::Null));
+        assert_eq!(
+            aggregator.aggregate(),
+            Some(rate(json!({"max": 300.0, "last": 300.0})))
+        );
+
+        aggregator.add(rate(json!(200.0)));
+        aggregator.add(rate(Value::Null));
+        aggregator.add(rate(json!(50.0)));
+        assert_eq!(
+            aggregator.aggregate(),
+            Some(rate(json!({"max": 200.0, "last": 50.0})))
+        );
+
+        aggregator.add(rate(Value::Null));
+        aggregator.add(rate(Value::Null));
+        assert_eq!(aggregator.aggregate(), Some(rate(Value::Null)));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::duration::deserialize_optional_duration;
+use crate::payload::{flatten_stats, section_topic};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Condition {
+    Above,
+    Below,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+// NOTE: 'metric' is the path of a value as published in the 'flat' publish
+//       mode, i.e. 'memory/used_bytes', and can contain the MQTT wildcards
+//       '+' and '#', every matching value is tracked as a separate alert
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: String,
+    pub condition: Condition,
+    pub threshold: f64,
+    pub clear_threshold: Option<f64>,
+    #[serde(
+        default,
+        rename = "for",
+        deserialize_with = "deserialize_optional_duration"
+    )]
+    pub duration: Option<Duration>,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl AlertRule {
+    fn matches_metric(&self, path: &str) -> bool {
+        let mut path_levels = path.split('/');
+        for filter_level in self.metric.split('/') {
+            match (filter_level, path_levels.next()) {
+                ("#", _) => return true,
+                ("+", Some(_)) => (),
+                (filter_level, Some(path_level)) if filter_level == path_level => (),
+                _ => return false,
+            }
+        }
+
+        path_levels.next().is_none()
+    }
+
+    fn is_triggered(&self, value: f64) -> bool {
+        match self.condition {
+            Condition::Above => value > self.threshold,
+            Condition::Below => value < self.threshold,
+        }
+    }
+
+    // NOTE: hysteresis, a firing alert is resolved only once the value crosses
+    //       the clear threshold, which defaults to the threshold
+    fn is_cleared(&self, value: f64) -> bool {
+        let clear_threshold = self.clear_threshold.unwrap_or(self.threshold);
+        match self.condition {
+            Condition::Above => value <= clear_threshold,
+            Condition::Below => value >= clear_threshold,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+pub struct AlertEvent {
+    pub name: String,
+    pub metric: String,
+    pub state: AlertState,
+    pub severity: Severity,
+    pub value: f64,
+    pub threshold: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum InstanceState {
+    Pending(Instant),
+    Firing(f64),
+}
+
+#[derive(Debug)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    // NOTE: state of alerts that are pending or firing, keyed by the rule index
+    //       and the path of the matched value
+    instances: HashMap<(usize, String), InstanceState>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> AlertEngine {
+        AlertEngine {
+            rules,
+            instances: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // NOTE: evaluates the rules against the values of the sampled resource
+    //       sections, and returns the alerts that started firing or were
+    //       resolved, alerts of values that are gone from a sampled section,
+    //       like the load of an exited process, are dropped, and the firing
+    //       ones are resolved with the last seen value, while alerts of values
+    //       that are 'null' for a sample, like a rate after a counter reset,
+    //       keep their state
+    pub fn evaluate(&mut self, stats: &Value, now: Instant) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        let values = flatten_stats(stats);
+        let sections: Vec<&str> = stats
+            .as_object()
+            .map(|stats| stats.keys().map(|key| section_topic(key)).collect())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            for (path, value) in values.iter().filter(|(path, _)| rule.matches_metric(path)) {
+                let key = (index, path.to_owned());
+                seen.insert(key.clone());
+                let value = match value.as_f64() {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let event = |state| AlertEvent {
+                    name: rule.name.to_owned(),
+                    metric: path.to_owned(),
+                    state,
+                    severity: rule.severity,
+                    value,
+                    threshold: rule.threshold,
+                };
+
+                match self.instances.get(&key).copied() {
+                    None if rule.is_triggered(value) => match rule.duration {
+                        Some(duration) if !duration.is_zero() => {
+                            self.instances.insert(key, InstanceState::Pending(now));
+                        }
+                        _ => {
+                            self.instances.insert(key, InstanceState::Firing(value));
+                            events.push(event(AlertState::Firing));
+                        }
+                    },
+                    None => (),
+                    Some(InstanceState::Pending(_)) if !rule.is_triggered(value) => {
+                        self.instances.remove(&key);
+                    }
+                    Some(InstanceState::Pending(since))
+                        if now.saturating_duration_since(since)
+                            >= rule.duration.unwrap_or_default() =>
+                    {
+                        self.instances.insert(key, InstanceState::Firing(value));
+                        events.push(event(AlertState::Firing));
+                    }
+                    Some(InstanceState::Firing(_)) if rule.is_cleared(value) => {
+                        self.instances.remove(&key);
+                        events.push(event(AlertState::Resolved));
+                    }
+                    Some(InstanceState::Firing(_)) => {
+                        self.instances.insert(key, InstanceState::Firing(value));
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+
+        let mut vanished: Vec<(usize, String)> = self
+            .instances
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .filter(|(_, path)| {
+                sections
+                    .iter()
+                    .any(|topic| path.starts_with(&format!("{}/", topic)))
+            })
+            .cloned()
+            .collect();
+        vanished.sort();
+        for key in vanished {
+            if let Some(InstanceState::Firing(value)) = self.instances.remove(&key) {
+                let rule = &self.rules[key.0];
+                events.push(AlertEvent {
+                    name: rule.name.to_owned(),
+                    metric: key.1,
+                    state: AlertState::Resolved,
+                    severity: rule.severity,
+                    value,
+                    threshold: rule.threshold,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(metric: &str, condition: Condition, threshold: f64) -> AlertRule {
+        AlertRule {
+            name: "test".to_owned(),
+            metric: metric.to_owned(),
+            condition,
+            threshold,
+            clear_threshold: None,
+            duration: None,
+            severity: Severity::Warning,
+        }
+    }
+
+    fn cpus(loads: &[f64]) -> Value {
+        json!({
+            "cpus": loads
+                .iter()
+                .enumerate()
+                .map(|(id, load)| json!({"id": id, "load": load}))
+                .collect::<Vec<Value>>()
+        })
+    }
+
+    fn states(events: &[AlertEvent]) -> Vec<(&str, AlertState)> {
+        events
+            .iter()
+            .map(|event| (event.metric.as_str(), event.state))
+            .collect()
+    }
+
+    #[test]
+    fn test_metric_matching() {
+        assert!(rule("cpu/0/load", Condition::Above, 0.0).matches_metric("cpu/0/load"));
+        assert!(!rule("cpu/0/load", Condition::Above, 0.0).matches_metric("cpu/1/load"));
+        assert!(rule("cpu/+/load", Condition::Above, 0.0).matches_metric("cpu/1/load"));
+        assert!(!rule("cpu/+", Condition::Above, 0.0).matches_metric("cpu/1/load"));
+        assert!(rule("network/#", Condition::Above, 0.0).matches_metric("network/eth0/rx_bytes"));
+        assert!(!rule("memory/used_bytes", Condition::Above, 0.0).matches_metric("memory"));
+    }
+
+    #[test]
+    fn test_immediate_alert() {
+        let start = Instant::now();
+        let mut engine = AlertEngine::new(vec![rule("cpu/+/load", Condition::Above, 85.0)]);
+
+        assert!(engine.evaluate(&cpus(&[10.0, 20.0]), start).is_empty());
+        assert_eq!(
+            states(&engine.evaluate(&cpus(&[90.0, 20.0]), start)),
+            vec![("cpu/0/load", AlertState::Firing)]
+        );
+        assert!(engine.evaluate(&cpus(&[95.0, 20.0]), start).is_empty());
+        assert_eq!(
+            states(&engine.evaluate(&cpus(&[50.0, 99.0]), start)),
+            vec![
+                ("cpu/0/load", AlertState::Resolved),
+                ("cpu/1/load", AlertState::Firing)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duration_condition() {
+        let start = Instant::now();
+        let mut alert = rule("cpu/0/load", Condition::Above, 85.0);
+        alert.duration = Some(Duration::from_secs(300));
+        let mut engine = AlertEngine::new(vec![alert]);
+
+        assert!(engine.evaluate(&cpus(&[90.0]), start).is_empty());
+        assert!(engine
+            .evaluate(&cpus(&[90.0]), start + Duration::from_secs(200))
+            .is_empty());
+        assert!(engine
+            .evaluate(&cpus(&[80.0]), start + Duration::from_secs(250))
+            .is_empty());
+        assert!(engine
+            .evaluate(&cpus(&[90.0]), start + Duration::from_secs(260))
+            .is_empty());
+        assert!(engine
+            .evaluate(&cpus(&[90.0]), start + Duration::from_secs(559))
+            .is_empty());
+
+        let events = engine.evaluate(&cpus(&[91.0]), start + Duration::from_secs(560));
+        assert_eq!(
+            events,
+            vec![AlertEvent {
+                name: "test".to_owned(),
+                metric: "cpu/0/load".to_owned(),
+                state: AlertState::Firing,
+                severity: Severity::Warning,
+                value: 91.0,
+                threshold: 85.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let start = Instant::now();
+        let mut alert = rule("memory/free_percent", Condition::Below, 10.0);
+        alert.clear_threshold = Some(15.0);
+        let mut engine = AlertEngine::new(vec![alert]);
+        let memory = |free_percent: f64| json!({"memory": {"free_percent": free_percent}});
+
+        assert_eq!(
+            states(&engine.evaluate(&memory(5.0), start)),
+            vec![("memory/free_percent", AlertState::Firing)]
+        );
+        assert!(engine.evaluate(&memory(12.0), start).is_empty());
+        assert!(engine.evaluate(&memory(8.0), start).is_empty());
+        assert!(engine.evaluate(&memory(14.9), start).is_empty());
+        assert_eq!(
+            states(&engine.evaluate(&memory(15.0), start)),
+            vec![("memory/free_percent", AlertState::Resolved)]
+        );
+        assert!(engine.evaluate(&memory(12.0), start).is_empty());
+    }
+
+    #[test]
+    fn test_vanished_values() {
+        let start = Instant::now();
+        let mut alert = rule("cpu/+/load", Condition::Above, 85.0);
+        alert.duration = Some(Duration::from_secs(60));
+        let mut engine = AlertEngine::new(vec![alert]);
+
+        assert!(engine.evaluate(&cpus(&[90.0, 95.0]), start).is_empty());
+        assert_eq!(
+            states(&engine.evaluate(&cpus(&[90.0, 96.0]), start + Duration::from_secs(60))),
+            vec![
+                ("cpu/0/load", AlertState::Firing),
+                ("cpu/1/load", AlertState::Firing)
+            ]
+        );
+        assert!(engine
+            .evaluate(&cpus(&[92.0, 97.0]), start + Duration::from_secs(70))
+            .is_empty());
+
+        let events = engine.evaluate(&cpus(&[93.0]), start + Duration::from_secs(80));
+        assert_eq!(states(&events), vec![("cpu/1/load", AlertState::Resolved)]);
+        assert_eq!(events[0].value, 97.0);
+        assert_eq!(engine.instances.len(), 1);
+
+        assert_eq!(
+            states(&engine.evaluate(&cpus(&[]), start + Duration::from_secs(90))),
+            vec![("cpu/0/load", AlertState::Resolved)]
+        );
+        assert!(engine.instances.is_empty());
+    }
+
+    #[test]
+    fn test_null_values() {
+        let start = Instant::now();
+        let mut engine = AlertEngine::new(vec![rule(
+            "network/+/rx_bytes_per_s",
+            Condition::Above,
+            100.0,
+        )]);
+        let rate =
+            |rate: Value| json!({"networks": [{"interface": "eth0", "rx_bytes_per_s": rate}]});
+
+        assert_eq!(
+            states(&engine.evaluate(&rate(json!(150.0)), start)),
+            vec![("network/eth0/rx_bytes_per_s", AlertState::Firing)]
+        );
+        assert!(engine.evaluate(&rate(Value::Null), start).is_empty());
+        assert!(engine.evaluate(&rate(json!(160.0)), start).is_empty());
+        assert_eq!(
+            states(&engine.evaluate(&rate(json!(50.0)), start)),
+            vec![("network/eth0/rx_bytes_per_s", AlertState::Resolved)]
+        );
+    }
+
+    #[test]
+    fn test_unsampled_sections() {
+        let start = Instant::now();
+        let mut engine = AlertEngine::new(vec![
+            rule("cpu/+/load", Condition::Above, 85.0),
+            rule("memory/used_bytes", Condition::Above, 10.0),
+        ]);
+
+        assert_eq!(
+            states(&engine.evaluate(&json!({"memory": {"used_bytes": 15}}), start)),
+            vec![("memory/used_bytes", AlertState::Firing)]
+        );
+        assert_eq!(
+            states(&engine.evaluate(&cpus(&[90.0]), start)),
+            vec![("cpu/0/load", AlertState::Firing)]
+        );
+        assert_eq!(engine.instances.len(), 2);
+        assert_eq!(
+            states(&engine.evaluate(&json!({"memory": {}}), start)),
+            vec![("memory/used_bytes", AlertState::Resolved)]
+        );
+        assert_eq!(engine.instances.len(), 1);
+    }
+
+    #[test]
+    fn test_alert_event_serialization() {
+        let event = AlertEvent {
+            name: "root_disk_full".to_owned(),
+            metric: "memory/used_bytes".to_owned(),
+            state: AlertState::Resolved,
+            severity: Severity::Critical,
+            value: 1.0,
+            threshold: 2.0,
+        };
+
+        assert_eq!(
+            serde_json::to_value(event).expect("serializable event"),
+            json!({
+                "name": "root_disk_full",
+                "metric": "memory/used_bytes",
+                "state": "resolved",
+                "severity": "critical",
+                "value": 1.0,
+                "threshold": 2.0
+            })
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::pattern::matches_pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+struct IoStat {
+    read_bytes: u64,
+    written_bytes: u64,
+    read_operations: u64,
+    write_operations: u64,
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+struct CgroupSample {
+    cpu_usage_us: u64,
+    memory_current_bytes: u64,
+    memory_max_bytes: Option<u64>,
+    io: IoStat,
+}
+
+// NOTE: 'cpu_usage' is relative to a single CPU, so it can exceed 100%, it and
+//       the IO values are counted since the previous sample, and are 'null'
+//       on the first sample of a cgroup, 'memory_max_bytes' is 'null' when
+//       the memory of the cgroup is not limited
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct CgroupResource {
+    pub path: String,
+    pub cpu_usage: Option<f64>,
+    pub memory_current_bytes: u64,
+    pub memory_max_bytes: Option<u64>,
+    pub io_read_bytes: Option<u64>,
+    pub io_written_bytes: Option<u64>,
+    pub io_read_operations: Option<u64>,
+    pub io_write_operations: Option<u64>,
+}
+
+fn parse_cpu_stat(content: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        line.strip_prefix("usage_usec ")
+            .and_then(|value| value.trim().parse().ok())
+    })
+}
+
+// NOTE: 'memory.max' holds 'max' when the memory is not limited
+fn parse_memory_max(content: &str) -> Option<u64> {
+    content.trim().parse().ok()
+}
+
+// NOTE: 'io.stat' has a line per device with 'key=value' pairs, the values of
+//       all devices are summed up
+fn parse_io_stat(content: &str) -> IoStat {
+    let mut io = IoStat::default();
+    for (key, value) in content
+        .split_whitespace()
+        .filter_map(|field| field.split_once('='))
+    {
+        let value: u64 = value.parse().unwrap_or(0);
+        match key {
+            "rbytes" => io.read_bytes += value,
+            "wbytes" => io.written_bytes += value,
+            "rios" => io.read_operations += value,
+            "wios" => io.write_operations += value,
+            _ => (),
+        }
+    }
+
+    io
+}
+
+fn read_sample(directory: &Path) -> Option<CgroupSample> {
+    let read = |file: &str| std::fs::read_to_string(directory.join(file)).ok();
+
+    Some(CgroupSample {
+        cpu_usage_us: parse_cpu_stat(&read("cpu.stat")?)?,
+        memory_current_bytes: read("memory.current")
+            .and_then(|content| content.trim().parse().ok())
+            .unwrap_or(0),
+        memory_max_bytes: read("memory.max").and_then(|content| parse_memory_max(&content)),
+        io: read("io.stat")
+            .map(|content| parse_io_stat(&content))
+            .unwrap_or_default(),
+    })
+}
+
+// NOTE: every level of a configured path can contain the '*' and '?'
+//       wildcards, i.e. 'system.slice/*.service' or 'docker/*'
+fn expand_path(root: &Path, pattern: &str) -> Vec<String> {
+    let mut paths = vec![String::new()];
+    for level in pattern.split('/').filter(|level| !level.is_empty()) {
+        paths = paths
+            .into_iter()
+            .flat_map(|path| {
+                let mut children: Vec<String> = match std::fs::read_dir(root.join(&path)) {
+                    Ok(entries) => entries
+                        .filter_map(|entry| entry.ok())
+                        .filter(|entry| entry.path().is_dir())
+                        .filter_map(|entry| entry.file_name().into_string().ok())
+                        .filter(|name| matches_pattern(level, name))
+                        .map(|name| match path.is_empty() {
+                            true => name,
+                            false => format!("{}/{}", path, name),
+                        })
+                        .collect(),
+                    Err(_) => Vec::new(),
+                };
+                children.sort();
+                children
+            })
+            .collect();
+    }
+
+    paths.into_iter().filter(|path| !path.is_empty()).collect()
+}
+
+fn to_resource(
+    path: &str,
+    sample: &CgroupSample,
+    previous: Option<&(Instant, CgroupSample)>,
+    now: Instant,
+) -> CgroupResource {
+    let delta = |current: u64, previous: u64| current.saturating_sub(previous);
+    let cpu_usage = previous.and_then(|(timestamp, previous)| {
+        let elapsed_us = now.saturating_duration_since(*timestamp).as_micros();
+        match elapsed_us {
+            0 => None,
+            elapsed_us => Some(
+                delta(sample.cpu_usage_us, previous.cpu_usage_us) as f64 * 100.0
+                    / elapsed_us as f64,
+            ),
+        }
+    });
+
+    CgroupResource {
+        path: path.to_owned(),
+        cpu_usage,
+        memory_current_bytes: sample.memory_current_bytes,
+        memory_max_bytes: sample.memory_max_bytes,
+        io_read_bytes: previous
+            .map(|(_, previous)| delta(sample.io.read_bytes, previous.io.read_bytes)),
+        io_written_bytes: previous
+            .map(|(_, previous)| delta(sample.io.written_bytes, previous.io.written_bytes)),
+        io_read_operations: previous
+            .map(|(_, previous)| delta(sample.io.read_operations, previous.io.read_operations)),
+        io_write_operations: previous
+            .map(|(_, previous)| delta(sample.io.write_operations, previous.io.write_operations)),
+    }
+}
+
+#[derive(Debug)]
+pub struct CgroupCollector {
+    root: PathBuf,
+    paths: Vec<String>,
+    // NOTE: sample of the previous collection and the time it was taken,
+    //       keyed by the cgroup path
+    previous: HashMap<String, (Instant, CgroupSample)>,
+}
+
+impl Default for CgroupCollector {
+    fn default() -> CgroupCollector {
+        CgroupCollector::new(Path::new(CGROUP_ROOT), &[])
+    }
+}
+
+impl CgroupCollector {
+    pub fn new(root: &Path, paths: &[String]) -> CgroupCollector {
+        CgroupCollector {
+            root: root.to_owned(),
+            paths: paths.to_vec(),
+            previous: HashMap::new(),
+        }
+    }
+
+    // NOTE: cgroups that don't exist, or are not cgroup v2 directories, are
+    //       skipped, as containers and services come and go
+    pub fn collect(&mut self, now: Instant) -> Vec<CgroupResource> {
+        let mut paths: Vec<String> = self
+            .paths
+            .iter()
+            .flat_map(|pattern| expand_path(&self.root, pattern))
+            .collect();
+        let mut seen = HashSet::new();
+        paths.retain(|path| seen.insert(path.to_owned()));
+
+        let samples: Vec<(String, CgroupSample)> = paths
+            .into_iter()
+            .filter_map(|path| read_sample(&self.root.join(&path)).map(|sample| (path, sample)))
+            .collect();
+        let resources = samples
+            .iter()
+            .map(|(path, sample)| to_resource(path, sample, self.previous.get(path), now))
+            .collect();
+
+        self.previous = samples
+            .into_iter()
+            .map(|(path, sample)| (path, (now, sample)))
+            .collect();
+
+        resources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sys/fs/cgroup");
+
+    #[test]
+    fn test_parse_io_stat() {
+        assert_eq!(
+            parse_io_stat(include_str!(
+                "../tests/fixtures/sys/fs/cgroup/system.slice/nginx.service/io.stat"
+            )),
+            IoStat {
+                read_bytes: 3145728,
+                written_bytes: 4194304,
+                read_operations: 52,
+                write_operations: 310,
+            }
+        );
+        assert_eq!(parse_io_stat(""), IoStat::default());
+    }
+
+    #[test]
+    fn test_parse_memory_max() {
+        assert_eq!(parse_memory_max("268435456\n"), Some(268435456));
+        assert_eq!(parse_memory_max("max\n"), None);
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let root = Path::new(FIXTURES);
+
+        assert_eq!(
+            expand_path(root, "system.slice/*.service"),
+            vec!["system.slice/nginx.service", "system.slice/tum.service"]
+        );
+        assert_eq!(expand_path(root, "/user.slice/"), vec!["user.slice"]);
+        assert!(expand_path(root, "docker/*").is_empty());
+    }
+
+    #[test]
+    fn test_collect() {
+        let start = Instant::now();
+        let mut collector = CgroupCollector::new(
+            Path::new(FIXTURES),
+            &[
+                "system.slice/nginx.service".to_owned(),
+                "user.slice".to_owned(),
+                "missing".to_owned(),
+                "user.*".to_owned(),
+            ],
+        );
+
+        assert_eq!(
+            collector.collect(start),
+            vec![
+                CgroupResource {
+                    path: "system.slice/nginx.service".to_owned(),
+                    cpu_usage: None,
+                    memory_current_bytes: 52871168,
+                    memory_max_bytes: Some(268435456),
+                    io_read_bytes: None,
+                    io_written_bytes: None,
+                    io_read_operations: None,
+                    io_write_operations: None,
+                },
+                CgroupResource {
+                    path: "user.slice".to_owned(),
+                    cpu_usage: None,
+                    memory_current_bytes: 1073741824,
+                    memory_max_bytes: None,
+                    io_read_bytes: None,
+                    io_written_bytes: None,
+                    io_read_operations: None,
+                    io_write_operations: None,
+                },
+            ]
+        );
+
+        let resources = collector.collect(start + Duration::from_secs(1));
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].cpu_usage, Some(0.0));
+        assert_eq!(resources[0].io_read_bytes, Some(0));
+        assert_eq!(resources[1].io_write_operations, Some(0));
+    }
+
+    #[test]
+    fn test_deltas() {
+        let start = Instant::now();
+        let previous = CgroupSample {
+            cpu_usage_us: 1_000_000,
+            memory_current_bytes: 100,
+            memory_max_bytes: None,
+            io: IoStat {
+                read_bytes: 1000,
+                written_bytes: 2000,
+                read_operations: 10,
+                write_operations: 20,
+            },
+        };
+        let current = CgroupSample {
+            cpu_usage_us: 4_000_000,
+            memory_current_bytes: 200,
+            memory_max_bytes: Some(1000),
+            io: IoStat {
+                read_bytes: 5000,
+                written_bytes: 2000,
+                read_operations: 14,
+                write_operations: 20,
+            },
+        };
+
+        assert_eq!(
+            to_resource(
+                "docker/abc",
+                &current,
+                Some(&(start, previous)),
+                start + Duration::from_secs(2)
+            ),
+            CgroupResource {
+                path: "docker/abc".to_owned(),
+                cpu_usage: Some(150.0),
+                memory_current_bytes: 200,
+                memory_max_bytes: Some(1000),
+                io_read_bytes: Some(4000),
+                io_written_bytes: Some(0),
+                io_read_operations: Some(4),
+                io_write_operations: Some(0),
+            }
+        );
+    }
+}
@@ -77,7 +77,7 @@ impl Tum {
             &tum_config.username,
             &tum_config.password,
         );
-        let resource_monitor = ResourceMonitor::new(tum_config.monitoring_rate_s, tum_mqtt_client);
+        let resource_monitor = ResourceMonitor::new(&tum_config, tum_mqtt_client)?;
 
         Ok(Tum { resource_monitor })
     }
@@ -1,21 +1,195 @@
+use crate::aggregation::Statistic;
+use crate::alert::{AlertRule, Condition};
+use crate::deadband::Deadband;
+use crate::docker::DOCKER_SOCKET;
+use crate::duration::{deserialize_duration, deserialize_optional_duration};
+use crate::pattern::matches_pattern;
+use crate::probe::{parse_http_url, ProbeKind};
+use crate::process::ProcessSort;
+use crate::resource::ResourceGroup;
+use crate::systemd::is_valid_unit_name;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
+use std::time::Duration;
 
 // TODO: think about making attributes public
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize, Default)]
 pub struct Configuration {
     pub username: String,
     pub password: String,
     pub server_addr: String,
     pub server_port: u16,
-    pub monitoring_rate_s: u64,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub monitoring_rate: Option<Duration>,
+    pub monitoring_rate_s: Option<u64>,
+    #[serde(default)]
+    pub align_to_wall_clock: bool,
+    #[serde(default)]
+    pub publish_mode: PublishMode,
+    #[serde(default)]
+    pub groups: HashMap<ResourceGroup, GroupConfiguration>,
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+    #[serde(default)]
+    pub sensors: SensorsConfiguration,
+    pub processes: Option<ProcessesConfiguration>,
+    #[serde(default)]
+    pub watch: Vec<WatchConfiguration>,
+    #[serde(default)]
+    pub systemd_units: Vec<String>,
+    #[serde(default)]
+    pub cgroups: Vec<String>,
+    pub docker: Option<DockerConfiguration>,
+    #[serde(default)]
+    pub probes: Vec<ProbeConfiguration>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Default)]
+pub struct GroupConfiguration {
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub monitoring_rate: Option<Duration>,
+    pub monitoring_rate_s: Option<u64>,
+    pub deadband: Option<Deadband>,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub max_silence: Option<Duration>,
+    pub aggregation: Option<AggregationConfiguration>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AggregationConfiguration {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub sampling_rate: Duration,
+    #[serde(default = "default_statistics")]
+    pub statistics: Vec<Statistic>,
+}
+
+// NOTE: sensors are selected by their label, which can contain the '*' and '?'
+//       wildcards, an empty 'include' list selects every sensor, and
+//       'exclude' takes precedence over 'include'
+#[derive(Debug, PartialEq, Deserialize, Default, Clone)]
+pub struct SensorsConfiguration {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub hwmon: bool,
+}
+
+impl SensorsConfiguration {
+    pub fn is_selected(&self, label: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, label))
+        };
+
+        (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+    }
+}
+
+// NOTE: the top 'count' processes are reported for every sort criterion
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct ProcessesConfiguration {
+    #[serde(default = "default_process_count")]
+    pub count: usize,
+    #[serde(default = "default_process_sort")]
+    pub sort_by: Vec<ProcessSort>,
+    #[serde(default = "default_cmdline_length")]
+    pub cmdline_length: usize,
+}
+
+// NOTE: a watched process is matched by the name of the process, by its
+//       command line, or by both, the patterns can contain the '*' and '?'
+//       wildcards, and every matching process is counted as an instance
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct WatchConfiguration {
+    pub name: String,
+    pub process: Option<String>,
+    pub cmdline: Option<String>,
+}
+
+impl WatchConfiguration {
+    pub fn matches(&self, process: &str, cmdline: &str) -> bool {
+        self.process
+            .as_ref()
+            .map(|pattern| matches_pattern(pattern, process))
+            .unwrap_or(true)
+            && self
+                .cmdline
+                .as_ref()
+                .map(|pattern| matches_pattern(pattern, cmdline))
+                .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct DockerConfiguration {
+    #[serde(default = "default_docker_socket")]
+    pub socket: PathBuf,
+}
+
+// NOTE: 'target' is 'host:port' for TCP probes, an 'http://' URL for HTTP
+//       probes and a host for ICMP probes, 'count' attempts are made on every
+//       sample, each of them limited by 'timeout'
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct ProbeConfiguration {
+    pub name: String,
+    pub kind: ProbeKind,
+    pub target: String,
+    #[serde(default = "default_probe_count")]
+    pub count: u32,
+    #[serde(
+        default = "default_probe_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub timeout: Duration,
+}
+
+fn default_docker_socket() -> PathBuf {
+    PathBuf::from(DOCKER_SOCKET)
+}
+
+fn default_probe_count() -> u32 {
+    1
+}
+
+fn default_probe_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_process_count() -> usize {
+    5
+}
+
+fn default_process_sort() -> Vec<ProcessSort> {
+    vec![ProcessSort::Cpu, ProcessSort::Memory]
+}
+
+fn default_cmdline_length() -> usize {
+    256
+}
+
+fn default_statistics() -> Vec<Statistic> {
+    Statistic::ALL.to_vec()
+}
+
+#[derive(Debug, PartialEq, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishMode {
+    #[default]
+    Stats,
+    Groups,
+    Flat,
 }
 
 #[derive(Debug)]
 pub enum ConfigurationError {
     FileOperation(std::io::Error),
     ParsingConfiguration(serde_yaml::Error),
+    Validation(String),
 }
 
 impl From<std::io::Error> for ConfigurationError {
@@ -35,15 +209,716 @@ impl fmt::Display for ConfigurationError {
         match *self {
             ConfigurationError::FileOperation(ref err) => write!(f, "{}", err),
             ConfigurationError::ParsingConfiguration(ref err) => write!(f, "{}", err),
+            ConfigurationError::Validation(ref err) => {
+                write!(f, "Invalid configuration: {}", err)
+            }
         }
     }
 }
 
+// NOTE: 'monitoring_rate' and the older 'monitoring_rate_s' are mutually
+//       exclusive, and neither of them can be 0
+fn resolve_monitoring_rate(
+    name: &str,
+    monitoring_rate: Option<Duration>,
+    monitoring_rate_s: Option<u64>,
+) -> Result<Option<Duration>, ConfigurationError> {
+    let resolved = match (monitoring_rate, monitoring_rate_s) {
+        (Some(_), Some(_)) => {
+            return Err(ConfigurationError::Validation(format!(
+                "'{0}monitoring_rate' and '{0}monitoring_rate_s' can't be both set",
+                name
+            )))
+        }
+        (Some(rate), None) => Some(rate),
+        (None, Some(rate_s)) => Some(Duration::from_secs(rate_s)),
+        (None, None) => None,
+    };
+
+    match resolved {
+        Some(rate) if rate.is_zero() => Err(ConfigurationError::Validation(format!(
+            "'{}monitoring_rate' must be greater than 0",
+            name
+        ))),
+        _ => Ok(resolved),
+    }
+}
+
 impl Configuration {
     pub fn from_file(configuration_path: &PathBuf) -> Result<Configuration, ConfigurationError> {
         let configuration_file = std::fs::File::open(configuration_path)?;
-        let configuration = serde_yaml::from_reader(configuration_file)?;
+        let configuration: Configuration = serde_yaml::from_reader(configuration_file)?;
+
+        configuration.validate()
+    }
+
+    fn validate(self) -> Result<Configuration, ConfigurationError> {
+        if resolve_monitoring_rate("", self.monitoring_rate, self.monitoring_rate_s)?.is_none() {
+            return Err(ConfigurationError::Validation(
+                "either 'monitoring_rate' or 'monitoring_rate_s' must be set".to_owned(),
+            ));
+        }
+
+        for (group, group_configuration) in &self.groups {
+            resolve_monitoring_rate(
+                &format!("groups.{}.", group.name()),
+                group_configuration.monitoring_rate,
+                group_configuration.monitoring_rate_s,
+            )?;
+
+            if let Some(ref aggregation) = group_configuration.aggregation {
+                if aggregation.sampling_rate.is_zero() {
+                    return Err(ConfigurationError::Validation(format!(
+                        "'groups.{}.aggregation.sampling_rate' must be greater than 0",
+                        group.name()
+                    )));
+                }
+                if aggregation.statistics.is_empty() {
+                    return Err(ConfigurationError::Validation(format!(
+                        "'groups.{}.aggregation.statistics' can't be empty",
+                        group.name()
+                    )));
+                }
+            }
+
+            if let Some(deadband) = group_configuration.deadband {
+                if deadband.absolute.unwrap_or(0.0) < 0.0 || deadband.percent.unwrap_or(0.0) < 0.0 {
+                    return Err(ConfigurationError::Validation(format!(
+                        "'groups.{}.deadband' thresholds can't be negative",
+                        group.name()
+                    )));
+                }
+            }
+        }
+
+        if let Some(ref processes) = self.processes {
+            if processes.count == 0 {
+                return Err(ConfigurationError::Validation(
+                    "'processes.count' must be greater than 0".to_owned(),
+                ));
+            }
+            if processes.sort_by.is_empty() {
+                return Err(ConfigurationError::Validation(
+                    "'processes.sort_by' can't be empty".to_owned(),
+                ));
+            }
+        }
+
+        for (i, watch) in self.watch.iter().enumerate() {
+            if watch.name.is_empty() || (watch.process.is_none() && watch.cmdline.is_none()) {
+                return Err(ConfigurationError::Validation(
+                    "'watch' entries must have a 'name', and a 'process' or a 'cmdline'".to_owned(),
+                ));
+            }
+            if self.watch[..i].iter().any(|other| other.name == watch.name) {
+                return Err(ConfigurationError::Validation(format!(
+                    "'watch' entry '{}' is defined more than once",
+                    watch.name
+                )));
+            }
+        }
+
+        if let Some(unit) = self
+            .systemd_units
+            .iter()
+            .find(|unit| !is_valid_unit_name(unit))
+        {
+            return Err(ConfigurationError::Validation(format!(
+                "'{}' of 'systemd_units' is not a valid unit name",
+                unit
+            )));
+        }
+
+        for (i, probe) in self.probes.iter().enumerate() {
+            if probe.name.is_empty() || probe.target.is_empty() {
+                return Err(ConfigurationError::Validation(
+                    "'probes' entries must have a 'name' and a 'target'".to_owned(),
+                ));
+            }
+            // NOTE: the name is a level of the topics of the probe
+            if probe.name.contains(['/', '+', '#']) {
+                return Err(ConfigurationError::Validation(format!(
+                    "'name' of probe '{}' can't contain '/', '+' or '#'",
+                    probe.name
+                )));
+            }
+            if self.probes[..i]
+                .iter()
+                .any(|other| other.name == probe.name)
+            {
+                return Err(ConfigurationError::Validation(format!(
+                    "'probes' entry '{}' is defined more than once",
+                    probe.name
+                )));
+            }
+            if probe.count == 0 || probe.timeout.is_zero() {
+                return Err(ConfigurationError::Validation(format!(
+                    "'count' and 'timeout' of probe '{}' must be greater than 0",
+                    probe.name
+                )));
+            }
+            if probe.kind == ProbeKind::Http && parse_http_url(&probe.target).is_none() {
+                return Err(ConfigurationError::Validation(format!(
+                    "'target' of probe '{}' must be an 'http://' URL",
+                    probe.name
+                )));
+            }
+            // NOTE: 'ping' takes the target as an argument, and the timeout in
+            //       whole seconds
+            if probe.kind == ProbeKind::Icmp && probe.target.starts_with('-') {
+                return Err(ConfigurationError::Validation(format!(
+                    "'target' of probe '{}' can't start with '-'",
+                    probe.name
+                )));
+            }
+            if probe.kind == ProbeKind::Icmp && probe.timeout.subsec_nanos() != 0 {
+                return Err(ConfigurationError::Validation(format!(
+                    "'timeout' of ICMP probe '{}' must be a whole number of seconds",
+                    probe.name
+                )));
+            }
+            if probe.kind == ProbeKind::Tcp
+                && probe
+                    .target
+                    .rsplit_once(':')
+                    .map(|(_, port)| port.parse::<u16>().is_err())
+                    .unwrap_or(true)
+            {
+                return Err(ConfigurationError::Validation(format!(
+                    "'target' of probe '{}' must be in the 'host:port' format",
+                    probe.name
+                )));
+            }
+        }
+
+        for alert in &self.alerts {
+            if alert.name.is_empty() || alert.metric.is_empty() {
+                return Err(ConfigurationError::Validation(
+                    "'alerts' must have a 'name' and a 'metric'".to_owned(),
+                ));
+            }
+
+            let is_clear_threshold_valid = match (alert.condition, alert.clear_threshold) {
+                (Condition::Above, Some(clear_threshold)) => clear_threshold <= alert.threshold,
+                (Condition::Below, Some(clear_threshold)) => clear_threshold >= alert.threshold,
+                (_, None) => true,
+            };
+            if !is_clear_threshold_valid {
+                return Err(ConfigurationError::Validation(format!(
+                    "'clear_threshold' of alert '{}' must not trigger the alert",
+                    alert.name
+                )));
+            }
+        }
+
+        Ok(self)
+    }
+
+    // NOTE: 'None' when neither rate is set, or the rate is 0, which only a
+    //       configuration that didn't go through 'validate' can have
+    pub fn monitoring_rate(&self) -> Option<Duration> {
+        self.monitoring_rate
+            .or(self.monitoring_rate_s.map(Duration::from_secs))
+            .filter(|rate| !rate.is_zero())
+    }
+
+    pub fn group_monitoring_rate(&self, group: ResourceGroup) -> Option<Duration> {
+        self.groups
+            .get(&group)
+            .and_then(|group| {
+                group
+                    .monitoring_rate
+                    .or(group.monitoring_rate_s.map(Duration::from_secs))
+            })
+            .filter(|rate| !rate.is_zero())
+            .or(self.monitoring_rate())
+    }
+
+    // NOTE: the 'gpu', 'power' and 'sockets' groups are collected only when
+    //       they are listed in 'groups', and the groups reported for an option
+    //       only when the option is set, so no collector runs for nothing
+    pub fn is_group_enabled(&self, group: ResourceGroup) -> bool {
+        match group {
+            ResourceGroup::Gpu | ResourceGroup::Power | ResourceGroup::Sockets => {
+                self.groups.contains_key(&group)
+            }
+            ResourceGroup::Cgroups => !self.cgroups.is_empty(),
+            ResourceGroup::Containers => self.docker.is_some(),
+            ResourceGroup::Probes => !self.probes.is_empty(),
+            ResourceGroup::Processes => self.processes.is_some(),
+            ResourceGroup::Systemd => !self.systemd_units.is_empty(),
+            ResourceGroup::Watch => !self.watch.is_empty(),
+            ResourceGroup::Cpu
+            | ResourceGroup::Memory
+            | ResourceGroup::Network
+            | ResourceGroup::Pressure
+            | ResourceGroup::Sensors
+            | ResourceGroup::System => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::Severity;
+
+    const CONNECTION: &str = "
+server_addr: localhost
+server_port: 1883
+username: tum
+password: tum
+";
+
+    fn parse(configuration: &str) -> Result<Configuration, ConfigurationError> {
+        let configuration: Configuration =
+            serde_yaml::from_str(&format!("{}{}", CONNECTION, configuration))?;
+        configuration.validate()
+    }
+
+    #[test]
+    fn test_monitoring_rate() {
+        let configuration = parse("monitoring_rate: 500ms").expect("valid configuration");
+        assert_eq!(
+            configuration.monitoring_rate(),
+            Some(Duration::from_millis(500))
+        );
+
+        let configuration = parse("monitoring_rate: 2").expect("valid configuration");
+        assert_eq!(
+            configuration.monitoring_rate(),
+            Some(Duration::from_secs(2))
+        );
+
+        let configuration = parse("monitoring_rate_s: 5").expect("valid configuration");
+        assert_eq!(
+            configuration.monitoring_rate(),
+            Some(Duration::from_secs(5))
+        );
+
+        assert_eq!(Configuration::default().monitoring_rate(), None);
+        let configuration = Configuration {
+            monitoring_rate_s: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(configuration.monitoring_rate(), None);
+    }
+
+    #[test]
+    fn test_group_monitoring_rate() {
+        let configuration = parse(
+            "
+monitoring_rate: 1m
+groups:
+  cpu:
+    monitoring_rate: 250ms
+  memory:
+    monitoring_rate_s: 10
+",
+        )
+        .expect("valid configuration");
+
+        assert_eq!(
+            configuration.group_monitoring_rate(ResourceGroup::Cpu),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            configuration.group_monitoring_rate(ResourceGroup::Memory),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            configuration.group_monitoring_rate(ResourceGroup::Network),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_enabled_groups() {
+        let configuration = parse(
+            "
+monitoring_rate: 1m
+groups:
+  sockets: {}
+systemd_units:
+  - ssh.service
+",
+        )
+        .expect("valid configuration");
+
+        assert!(configuration.is_group_enabled(ResourceGroup::Cpu));
+        assert!(configuration.is_group_enabled(ResourceGroup::Sockets));
+        assert!(configuration.is_group_enabled(ResourceGroup::Systemd));
+        assert!(!configuration.is_group_enabled(ResourceGroup::Gpu));
+        assert!(!configuration.is_group_enabled(ResourceGroup::Power));
+        assert!(!configuration.is_group_enabled(ResourceGroup::Watch));
+    }
+
+    #[test]
+    fn test_invalid_monitoring_rate() {
+        assert!(matches!(
+            parse("monitoring_rate_s: 0"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 0ms"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nmonitoring_rate_s: 1"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("publish_mode: flat"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\ngroups:\n  cpu:\n    monitoring_rate: 0s"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1x"),
+            Err(ConfigurationError::ParsingConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn test_deadband() {
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+groups:
+  memory:
+    deadband:
+      percent: 2.5
+    max_silence: 5m
+",
+        )
+        .expect("valid configuration");
+        let memory = &configuration.groups[&ResourceGroup::Memory];
+
+        assert_eq!(
+            memory.deadband,
+            Some(Deadband {
+                absolute: None,
+                percent: Some(2.5)
+            })
+        );
+        assert_eq!(memory.max_silence, Some(Duration::from_secs(300)));
+
+        assert!(matches!(
+            parse("monitoring_rate: 1s\ngroups:\n  cpu:\n    deadband:\n      absolute: -1"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let configuration = parse(
+            "
+monitoring_rate: 1m
+groups:
+  cpu:
+    aggregation:
+      sampling_rate: 1s
+  memory:
+    aggregation:
+      sampling_rate: 5s
+      statistics: [max, p95]
+",
+        )
+        .expect("valid configuration");
+
+        assert_eq!(
+            configuration.groups[&ResourceGroup::Cpu].aggregation,
+            Some(AggregationConfiguration {
+                sampling_rate: Duration::from_secs(1),
+                statistics: Statistic::ALL.to_vec()
+            })
+        );
+        assert_eq!(
+            configuration.groups[&ResourceGroup::Memory].aggregation,
+            Some(AggregationConfiguration {
+                sampling_rate: Duration::from_secs(5),
+                statistics: vec![Statistic::Max, Statistic::P95]
+            })
+        );
+
+        assert!(matches!(
+            parse(
+                "monitoring_rate: 1m\ngroups:\n  cpu:\n    aggregation:\n      sampling_rate: 0s"
+            ),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1m\ngroups:\n  cpu:\n    aggregation:\n      sampling_rate: 1s\n      statistics: []"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_alerts() {
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+alerts:
+  - name: high_cpu_load
+    metric: cpu/+/load
+    condition: above
+    threshold: 85
+    clear_threshold: 80
+    for: 5m
+    severity: critical
+  - name: low_memory
+    metric: memory/used_bytes
+    condition: below
+    threshold: 1000
+",
+        )
+        .expect("valid configuration");
+
+        assert_eq!(configuration.alerts.len(), 2);
+        assert_eq!(
+            configuration.alerts[0].duration,
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(configuration.alerts[1].severity, Severity::Warning);
+
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nalerts:\n  - name: a\n    metric: cpu/0/load\n    condition: above\n    threshold: 85\n    clear_threshold: 90"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nalerts:\n  - name: ''\n    metric: cpu/0/load\n    condition: above\n    threshold: 85"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_sensors() {
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+sensors:
+  include: ['coretemp *', 'acpitz*']
+  exclude: ['coretemp Core ?']
+  hwmon: true
+",
+        )
+        .expect("valid configuration");
+
+        assert!(configuration.sensors.hwmon);
+        assert!(configuration.sensors.is_selected("coretemp Package id 0"));
+        assert!(configuration.sensors.is_selected("coretemp Core 12"));
+        assert!(!configuration.sensors.is_selected("coretemp Core 1"));
+        assert!(configuration.sensors.is_selected("acpitz temp1"));
+        assert!(!configuration.sensors.is_selected("nvme Composite"));
+
+        let configuration = parse("monitoring_rate: 1s").expect("valid configuration");
+        assert_eq!(configuration.sensors, SensorsConfiguration::default());
+        assert!(configuration.sensors.is_selected("nvme Composite"));
+    }
+
+    #[test]
+    fn test_processes() {
+        let configuration =
+            parse("monitoring_rate: 1s\nprocesses: {}").expect("valid configuration");
+        assert_eq!(
+            configuration.processes,
+            Some(ProcessesConfiguration {
+                count: 5,
+                sort_by: vec![ProcessSort::Cpu, ProcessSort::Memory],
+                cmdline_length: 256,
+            })
+        );
+
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+processes:
+  count: 10
+  sort_by: [disk]
+  cmdline_length: 64
+",
+        )
+        .expect("valid configuration");
+        assert_eq!(
+            configuration.processes,
+            Some(ProcessesConfiguration {
+                count: 10,
+                sort_by: vec![ProcessSort::Disk],
+                cmdline_length: 64,
+            })
+        );
+
+        assert_eq!(
+            parse("monitoring_rate: 1s")
+                .expect("valid configuration")
+                .processes,
+            None
+        );
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprocesses:\n  count: 0"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprocesses:\n  sort_by: []"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_watch() {
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+watch:
+  - name: postgres
+    process: postgres
+  - name: api
+    process: python3*
+    cmdline: '* -m api.server*'
+",
+        )
+        .expect("valid configuration");
+
+        assert_eq!(configuration.watch.len(), 2);
+        assert!(configuration.watch[0].matches("postgres", "postgres: checkpointer"));
+        assert!(!configuration.watch[0].matches("postgres-exporter", ""));
+        assert!(configuration.watch[1].matches("python3.11", "python3 -m api.server --port 80"));
+        assert!(!configuration.watch[1].matches("python3.11", "python3 -m http.server"));
+
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nwatch:\n  - name: nginx"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nwatch:\n  - name: nginx\n    process: nginx\n  - name: nginx\n    process: nginx*"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_systemd_units() {
+        let configuration = parse("monitoring_rate: 1s\nsystemd_units: [nginx, tum.service]")
+            .expect("valid configuration");
+        assert_eq!(
+            configuration.systemd_units,
+            vec!["nginx".to_owned(), "tum.service".to_owned()]
+        );
+
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nsystemd_units: ['']"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nsystemd_units: [nginx, 'bad name']"),
+            Err(ConfigurationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_cgroups() {
+        let configuration =
+            parse("monitoring_rate: 1s\ncgroups: [system.slice/*.service, docker/*]")
+                .expect("valid configuration");
+        assert_eq!(
+            configuration.cgroups,
+            vec!["system.slice/*.service".to_owned(), "docker/*".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_docker() {
+        let configuration = parse("monitoring_rate: 1s\ndocker: {}").expect("valid configuration");
+        assert_eq!(
+            configuration.docker,
+            Some(DockerConfiguration {
+                socket: PathBuf::from("/var/run/docker.sock")
+            })
+        );
+
+        let configuration =
+            parse("monitoring_rate: 1s\ndocker:\n  socket: /run/podman/podman.sock")
+                .expect("valid configuration");
+        assert_eq!(
+            configuration.docker,
+            Some(DockerConfiguration {
+                socket: PathBuf::from("/run/podman/podman.sock")
+            })
+        );
+    }
+
+    #[test]
+    fn test_probes() {
+        let configuration = parse(
+            "
+monitoring_rate: 1s
+probes:
+  - name: broker
+    kind: tcp
+    target: broker.local:1883
+  - name: api
+    kind: http
+    target: http://10.0.0.2:8080/health
+    count: 3
+    timeout: 500ms
+",
+        )
+        .expect("valid configuration");
+        assert_eq!(
+            configuration.probes,
+            vec![
+                ProbeConfiguration {
+                    name: "broker".to_owned(),
+                    kind: ProbeKind::Tcp,
+                    target: "broker.local:1883".to_owned(),
+                    count: 1,
+                    timeout: Duration::from_secs(1),
+                },
+                ProbeConfiguration {
+                    name: "api".to_owned(),
+                    kind: ProbeKind::Http,
+                    target: "http://10.0.0.2:8080/health".to_owned(),
+                    count: 3,
+                    timeout: Duration::from_millis(500),
+                },
+            ]
+        );
+        assert!(parse("monitoring_rate: 1s").unwrap().probes.is_empty());
 
-        Ok(configuration)
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: tcp, target: broker.local}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: http, target: 'https://a'}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: icmp, target: a, count: 0}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: icmp, target: a}, {name: a, kind: icmp, target: b}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: 'web/api', kind: icmp, target: a}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: icmp, target: '-f'}]"),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse(
+                "monitoring_rate: 1s\nprobes: [{name: a, kind: icmp, target: a, timeout: 500ms}]"
+            ),
+            Err(ConfigurationError::Validation(_))
+        ));
+        assert!(matches!(
+            parse("monitoring_rate: 1s\nprobes: [{name: a, kind: udp, target: a}]"),
+            Err(ConfigurationError::ParsingConfiguration(_))
+        ));
     }
 }
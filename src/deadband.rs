@@ -0,0 +1,257 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+// NOTE: cumulative counters and timestamps of the resources, which grow on
+//       every sample and are left out of the comparison, so the deadband
+//       applies to the gauges next to them, like the rates of an interface
+const COUNTER_FIELDS: &[&str] = &[
+    // networks
+    "rx_bytes",
+    "tx_bytes",
+    "rx_packets",
+    "tx_packets",
+    "rx_errors",
+    "tx_errors",
+    "rx_dropped",
+    "tx_dropped",
+    // sockets
+    "active_opens",
+    "passive_opens",
+    "attempt_fails",
+    "established_resets",
+    "in_segments",
+    "out_segments",
+    "retransmitted_segments",
+    "in_errors",
+    "out_resets",
+    "in_datagrams",
+    "out_datagrams",
+    "no_ports",
+    "receive_buffer_errors",
+    "send_buffer_errors",
+    // pressure
+    "total_us",
+    // containers
+    "network_rx_bytes",
+    "network_tx_bytes",
+    // probes
+    "completed_at",
+];
+
+// NOTE: a numeric value is considered changed when it differs from the last
+//       published value by more than every configured threshold, thresholds
+//       that are not configured are not checked
+#[derive(Debug, PartialEq, Deserialize, Default, Clone, Copy)]
+pub struct Deadband {
+    pub absolute: Option<f64>,
+    pub percent: Option<f64>,
+}
+
+impl Deadband {
+    fn is_number_changed(&self, old: f64, new: f64) -> bool {
+        let difference = (new - old).abs();
+        let exceeds_absolute = self
+            .absolute
+            .map(|absolute| difference > absolute)
+            .unwrap_or(true);
+        let exceeds_percent = self
+            .percent
+            .map(|percent| difference > old.abs() * percent / 100.0)
+            .unwrap_or(true);
+
+        exceeds_absolute && exceeds_percent
+    }
+
+    // NOTE: values that are not numbers are compared for equality, and any
+    //       structural difference, like an added network interface, is a change,
+    //       while the values of cumulative counters are not compared
+    pub fn is_changed(&self, old: &Value, new: &Value) -> bool {
+        match (old, new) {
+            (Value::Number(old), Value::Number(new)) => match (old.as_f64(), new.as_f64()) {
+                (Some(old), Some(new)) => self.is_number_changed(old, new),
+                _ => old != new,
+            },
+            (Value::Object(old), Value::Object(new)) => {
+                old.len() != new.len()
+                    || new.iter().any(|(key, new)| match old.get(key) {
+                        Some(_) if COUNTER_FIELDS.contains(&key.as_str()) => false,
+                        Some(old) => self.is_changed(old, new),
+                        None => true,
+                    })
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                old.len() != new.len()
+                    || old
+                        .iter()
+                        .zip(new.iter())
+                        .any(|(old, new)| self.is_changed(old, new))
+            }
+            (old, new) => old != new,
+        }
+    }
+}
+
+// NOTE: report-by-exception filter of a single resource group, the group is
+//       published only when it changed by more than the deadband, or when it
+//       has not been published for 'max_silence', cumulative counters are
+//       published along with the group, but don't make it change
+#[derive(Debug, Default)]
+pub struct ChangeFilter {
+    deadband: Option<Deadband>,
+    max_silence: Option<Duration>,
+    last_published: Option<(Value, Instant)>,
+}
+
+impl ChangeFilter {
+    pub fn new(deadband: Option<Deadband>, max_silence: Option<Duration>) -> ChangeFilter {
+        ChangeFilter {
+            deadband,
+            max_silence,
+            last_published: None,
+        }
+    }
+
+    pub fn should_publish(&mut self, value: &Value, now: Instant) -> bool {
+        let should_publish = match (&self.deadband, &self.last_published) {
+            (Some(deadband), Some((last_value, last_time))) => {
+                let is_silence_exceeded = self
+                    .max_silence
+                    .map(|max_silence| now.saturating_duration_since(*last_time) >= max_silence)
+                    .unwrap_or(false);
+
+                is_silence_exceeded || deadband.is_changed(last_value, value)
+            }
+            _ => true,
+        };
+
+        if should_publish && self.deadband.is_some() {
+            self.last_published = Some((value.clone(), now));
+        }
+
+        should_publish
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_absolute_deadband() {
+        let deadband = Deadband {
+            absolute: Some(10.0),
+            percent: None,
+        };
+
+        assert!(!deadband.is_changed(&json!({"load": 50.0}), &json!({"load": 60.0})));
+        assert!(deadband.is_changed(&json!({"load": 50.0}), &json!({"load": 60.5})));
+        assert!(deadband.is_changed(&json!({"load": 50.0}), &json!({"load": 39.0})));
+    }
+
+    #[test]
+    fn test_percent_deadband() {
+        let deadband = Deadband {
+            absolute: None,
+            percent: Some(5.0),
+        };
+
+        assert!(!deadband.is_changed(
+            &json!([{"used_bytes": 1000}]),
+            &json!([{"used_bytes": 1050}])
+        ));
+        assert!(deadband.is_changed(
+            &json!([{"used_bytes": 1000}]),
+            &json!([{"used_bytes": 1051}])
+        ));
+    }
+
+    #[test]
+    fn test_combined_deadband() {
+        let deadband = Deadband {
+            absolute: Some(100.0),
+            percent: Some(5.0),
+        };
+
+        assert!(!deadband.is_changed(&json!(1000), &json!(1080)));
+        assert!(!deadband.is_changed(&json!(10000), &json!(10200)));
+        assert!(deadband.is_changed(&json!(1000), &json!(1200)));
+    }
+
+    #[test]
+    fn test_structural_change() {
+        let deadband = Deadband {
+            absolute: Some(100.0),
+            percent: None,
+        };
+
+        assert!(deadband.is_changed(
+            &json!([{"interface": "eth0", "rx_bytes": 1}]),
+            &json!([{"interface": "eth1", "rx_bytes": 1}])
+        ));
+        assert!(deadband.is_changed(
+            &json!([{"interface": "eth0", "rx_bytes": 1}]),
+            &json!([
+                {"interface": "eth0", "rx_bytes": 1},
+                {"interface": "eth1", "rx_bytes": 1}
+            ])
+        ));
+        assert!(deadband.is_changed(&json!({"load": 1}), &json!({"usage": 1})));
+    }
+
+    #[test]
+    fn test_change_filter() {
+        let start = Instant::now();
+        let mut filter = ChangeFilter::new(
+            Some(Deadband {
+                absolute: Some(5.0),
+                percent: None,
+            }),
+            Some(Duration::from_secs(60)),
+        );
+
+        assert!(filter.should_publish(&json!({"load": 10.0}), start));
+        assert!(!filter.should_publish(&json!({"load": 14.0}), start + Duration::from_secs(10)));
+        assert!(!filter.should_publish(&json!({"load": 6.0}), start + Duration::from_secs(20)));
+        assert!(filter.should_publish(&json!({"load": 16.0}), start + Duration::from_secs(30)));
+        assert!(!filter.should_publish(&json!({"load": 16.0}), start + Duration::from_secs(89)));
+        assert!(filter.should_publish(&json!({"load": 16.0}), start + Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_network_counters_are_not_compared() {
+        let start = Instant::now();
+        let mut filter = ChangeFilter::new(
+            Some(Deadband {
+                absolute: Some(1000.0),
+                percent: None,
+            }),
+            None,
+        );
+        let networks = |rx_bytes: u64, rx_packets: u64, rx_bytes_per_s: f64| {
+            json!({
+                "networks": [{
+                    "interface": "eth0",
+                    "rx_bytes": rx_bytes,
+                    "rx_packets": rx_packets,
+                    "rx_bytes_per_s": rx_bytes_per_s
+                }]
+            })
+        };
+
+        assert!(filter.should_publish(&networks(1000000, 1000, 500.0), start));
+        assert!(!filter.should_publish(&networks(1005000, 1010, 500.0), start));
+        assert!(!filter.should_publish(&networks(1010000, 1020, 900.0), start));
+        assert!(filter.should_publish(&networks(1040000, 1050, 3000.0), start));
+    }
+
+    #[test]
+    fn test_change_filter_without_deadband() {
+        let start = Instant::now();
+        let mut filter = ChangeFilter::new(None, Some(Duration::from_secs(60)));
+
+        assert!(filter.should_publish(&json!({"load": 10.0}), start));
+        assert!(filter.should_publish(&json!({"load": 10.0}), start));
+    }
+}
@@ -0,0 +1,566 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+
+// NOTE: bound of a single request, and of all requests of one 'collect'
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const COLLECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum DockerError {
+    Connection(std::io::Error),
+    Http(String),
+    Parsing(serde_json::Error),
+}
+
+impl From<std::io::Error> for DockerError {
+    fn from(item: std::io::Error) -> DockerError {
+        DockerError::Connection(item)
+    }
+}
+
+impl From<serde_json::Error> for DockerError {
+    fn from(item: serde_json::Error) -> DockerError {
+        DockerError::Parsing(item)
+    }
+}
+
+impl fmt::Display for DockerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DockerError::Connection(ref err) => write!(f, "Docker engine connection: {}", err),
+            DockerError::Http(ref err) => write!(f, "Docker engine response: {}", err),
+            DockerError::Parsing(ref err) => write!(f, "Docker engine response parsing: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerSummary {
+    id: String,
+    names: Vec<String>,
+    image: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CpuUsage {
+    total_usage: u64,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CpuStats {
+    #[serde(default)]
+    cpu_usage: CpuUsage,
+    system_cpu_usage: Option<u64>,
+    online_cpus: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryStats {
+    usage: Option<u64>,
+    limit: Option<u64>,
+    #[serde(default)]
+    stats: HashMap<String, u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NetworkStats {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerStats {
+    #[serde(default)]
+    cpu_stats: CpuStats,
+    #[serde(default)]
+    memory_stats: MemoryStats,
+    #[serde(default)]
+    networks: HashMap<String, NetworkStats>,
+}
+
+// NOTE: 'cpu_usage' is the share of all CPUs of the host, multiplied by the
+//       number of CPUs, the same as reported by 'docker stats', it is 'null'
+//       on the first sample of a container, 'memory_used_bytes' doesn't
+//       include the inactive page cache, and the network counters are summed
+//       over all interfaces of the container
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct ContainerResource {
+    pub name: String,
+    pub id: String,
+    pub image: String,
+    pub cpu_usage: Option<f64>,
+    pub memory_used_bytes: u64,
+    pub memory_limit_bytes: Option<u64>,
+    pub network_rx_bytes: u64,
+    pub network_tx_bytes: u64,
+}
+
+fn decode_chunked(body: &[u8]) -> Result<Vec<u8>, DockerError> {
+    let mut decoded = Vec::new();
+    let mut rest = body;
+    loop {
+        let line_end = find(rest, b"\r\n")
+            .ok_or_else(|| DockerError::Http("truncated chunk size".to_owned()))?;
+        let size = std::str::from_utf8(&rest[..line_end])
+            .ok()
+            .and_then(|line| line.split(';').next())
+            .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+            .ok_or_else(|| DockerError::Http("invalid chunk size".to_owned()))?;
+        rest = &rest[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if rest.len() < size {
+            return Err(DockerError::Http("truncated chunk".to_owned()));
+        }
+        decoded.extend_from_slice(&rest[..size]);
+        rest = rest[size..].strip_prefix(b"\r\n").unwrap_or(&rest[size..]);
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+// NOTE: returns the body of a complete HTTP/1.1 response, only successful
+//       responses are accepted
+fn parse_http_response(response: &[u8]) -> Result<Vec<u8>, DockerError> {
+    let header_end = find(response, b"\r\n\r\n")
+        .ok_or_else(|| DockerError::Http("missing end of headers".to_owned()))?;
+    let head = String::from_utf8_lossy(&response[..header_end]);
+    let body = &response[header_end + 4..];
+
+    let mut lines = head.lines();
+    let status = lines.next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => (),
+        _ => return Err(DockerError::Http(format!("unexpected status '{}'", status))),
+    }
+
+    let is_chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    match is_chunked {
+        true => decode_chunked(body),
+        false => Ok(body.to_vec()),
+    }
+}
+
+fn timed_out() -> DockerError {
+    DockerError::Connection(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "request timed out",
+    ))
+}
+
+// NOTE: the response has to be complete before the deadline, the timeout of
+//       every read is shortened to the time that is left
+#[cfg(unix)]
+fn http_get(socket: &Path, path: &str, deadline: Instant) -> Result<Vec<u8>, DockerError> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let remaining = || {
+        Some(deadline.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(timed_out)
+    };
+
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_write_timeout(Some(remaining()?))?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    )?;
+
+    let mut response = Vec::new();
+    let mut buffer = [0; 8192];
+    loop {
+        stream.set_read_timeout(Some(remaining()?))?;
+        match stream.read(&mut buffer)? {
+            0 => break,
+            read => response.extend_from_slice(&buffer[..read]),
+        }
+    }
+    parse_http_response(&response)
+}
+
+#[cfg(not(unix))]
+fn http_get(_socket: &Path, _path: &str, _deadline: Instant) -> Result<Vec<u8>, DockerError> {
+    Err(DockerError::Connection(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    )))
+}
+
+fn cpu_usage(current: &CpuStats, previous: Option<&(u64, u64)>) -> Option<f64> {
+    let (previous_total, previous_system) = previous?;
+    let system_delta = current.system_cpu_usage?.checked_sub(*previous_system)?;
+    let total_delta = current.cpu_usage.total_usage.checked_sub(*previous_total)?;
+    match system_delta {
+        0 => None,
+        system_delta => Some(
+            total_delta as f64 / system_delta as f64
+                * current.online_cpus.unwrap_or(1) as f64
+                * 100.0,
+        ),
+    }
+}
+
+#[derive(Debug)]
+pub struct DockerCollector {
+    socket: PathBuf,
+    // NOTE: total CPU time of the container and of the host at the previous
+    //       sample, keyed by the container id
+    previous: HashMap<String, (u64, u64)>,
+}
+
+impl DockerCollector {
+    pub fn new(socket: &Path) -> DockerCollector {
+        DockerCollector {
+            socket: socket.to_owned(),
+            previous: HashMap::new(),
+        }
+    }
+
+    fn container_stats(&self, id: &str, deadline: Instant) -> Result<ContainerStats, DockerError> {
+        let stats = http_get(
+            &self.socket,
+            &format!("/containers/{}/stats?stream=false&one-shot=true", id),
+            deadline.min(Instant::now() + REQUEST_TIMEOUT),
+        )?;
+
+        Ok(serde_json::from_slice(&stats)?)
+    }
+
+    // NOTE: reports the running containers sorted by name, stats are requested
+    //       with 'one-shot', so the engine doesn't wait for a second sample,
+    //       containers whose stats can't be read before the deadline, like one
+    //       that stopped after it was listed, are skipped
+    pub fn collect(&mut self) -> Result<Vec<ContainerResource>, DockerError> {
+        let deadline = Instant::now() + COLLECT_TIMEOUT;
+        let containers: Vec<ContainerSummary> = serde_json::from_slice(&http_get(
+            &self.socket,
+            "/containers/json",
+            deadline.min(Instant::now() + REQUEST_TIMEOUT),
+        )?)?;
+
+        let mut previous = HashMap::new();
+        let mut resources = Vec::new();
+        for container in containers {
+            let stats = match self.container_stats(&container.id, deadline) {
+                Ok(stats) => stats,
+                Err(DockerError::Http(_)) => continue,
+                Err(err) => {
+                    eprintln!(
+                        "WRN: failed to collect stats of container '{}': {}",
+                        container.id.chars().take(12).collect::<String>(),
+                        err
+                    );
+                    if let Some(times) = self.previous.remove(&container.id) {
+                        previous.insert(container.id, times);
+                    }
+                    continue;
+                }
+            };
+
+            let memory = &stats.memory_stats;
+            let cache = memory
+                .stats
+                .get("inactive_file")
+                .or(memory.stats.get("total_inactive_file"))
+                .copied()
+                .unwrap_or(0);
+            resources.push(ContainerResource {
+                name: container
+                    .names
+                    .first()
+                    .map(|name| name.trim_start_matches('/').to_owned())
+                    .unwrap_or(container.id.chars().take(12).collect()),
+                id: container.id.chars().take(12).collect(),
+                image: container.image,
+                cpu_usage: cpu_usage(&stats.cpu_stats, self.previous.get(&container.id)),
+                memory_used_bytes: memory.usage.unwrap_or(0).saturating_sub(cache),
+                memory_limit_bytes: memory.limit,
+                network_rx_bytes: stats
+                    .networks
+                    .values()
+                    .map(|network| network.rx_bytes)
+                    .sum(),
+                network_tx_bytes: stats
+                    .networks
+                    .values()
+                    .map(|network| network.tx_bytes)
+                    .sum(),
+            });
+            if let Some(system_cpu_usage) = stats.cpu_stats.system_cpu_usage {
+                previous.insert(
+                    container.id,
+                    (stats.cpu_stats.cpu_usage.total_usage, system_cpu_usage),
+                );
+            }
+        }
+        self.previous = previous;
+        resources.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(resources)
+    }
+}
+
+// NOTE: collects the containers on a thread of its own, so a slow or hung
+//       engine doesn't delay the sampling of the other groups, every 'request'
+//       starts a collection unless one is already running, and 'results'
+//       returns the last completed one, 'None' when it failed, the thread
+//       stops once the runner is dropped
+#[derive(Debug)]
+pub struct DockerRunner {
+    requests: SyncSender<()>,
+    results: Arc<Mutex<Option<Vec<ContainerResource>>>>,
+}
+
+impl DockerRunner {
+    pub fn new(socket: &Path) -> DockerRunner {
+        let (requests, pending): (SyncSender<()>, Receiver<()>) = mpsc::sync_channel(1);
+        let results = Arc::new(Mutex::new(None));
+
+        let mut collector = DockerCollector::new(socket);
+        let worker_results = Arc::clone(&results);
+        thread::spawn(move || {
+            while pending.recv().is_ok() {
+                let containers = match collector.collect() {
+                    Ok(containers) => Some(containers),
+                    Err(err) => {
+                        eprintln!("WRN: failed to collect container stats: {}", err);
+                        None
+                    }
+                };
+                match worker_results.lock() {
+                    Ok(mut results) => *results = containers,
+                    Err(_) => return,
+                }
+            }
+        });
+
+        DockerRunner { requests, results }
+    }
+
+    pub fn request(&self) {
+        let _ = self.requests.try_send(());
+    }
+
+    pub fn results(&self) -> Option<Vec<ContainerResource>> {
+        match self.results.lock() {
+            Ok(results) => results.clone(),
+            Err(_) => None,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    const CONTAINERS: &str = include_str!("../tests/fixtures/docker/containers.json");
+    const STATS: &str = include_str!("../tests/fixtures/docker/stats.json");
+    const STATS_NEXT: &str = include_str!("../tests/fixtures/docker/stats-next.json");
+
+    // NOTE: serves 'connections' requests with the fixtures, the stats are
+    //       sent chunked, like the engine does, and advance after the first
+    //       listing of the containers, requests for the stats of the 'hung'
+    //       container are never answered
+    fn fake_engine(name: &str, connections: usize, hung: Option<&'static str>) -> PathBuf {
+        let socket = std::env::temp_dir().join(format!("tum-{}-{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).expect("bound fake engine socket");
+
+        thread::spawn(move || {
+            let mut hung_streams = Vec::new();
+            let mut listings = 0;
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.expect("accepted connection");
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .expect("request line");
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                if hung.is_some_and(|id| path.contains(id)) {
+                    hung_streams.push(stream);
+                    continue;
+                }
+
+                let response = match Some(path) {
+                    Some("/containers/json") => {
+                        listings += 1;
+                        format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                            CONTAINERS.len(),
+                            CONTAINERS
+                        )
+                    }
+                    Some(path) if path.starts_with("/containers/") => {
+                        let stats = match listings {
+                            1 => STATS,
+                            _ => STATS_NEXT,
+                        };
+                        let (first, second) = stats.split_at(stats.len() / 2);
+                        format!(
+                            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                            first.len(),
+                            first,
+                            second.len(),
+                            second
+                        )
+                    }
+                    _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_owned(),
+                };
+                stream.write_all(response.as_bytes()).expect("response");
+            }
+            if !hung_streams.is_empty() {
+                thread::sleep(COLLECT_TIMEOUT);
+            }
+        });
+
+        socket
+    }
+
+    #[test]
+    fn test_collect() {
+        let socket = fake_engine("docker-collect", 6, None);
+        let mut collector = DockerCollector::new(&socket);
+
+        let containers = collector.collect().expect("container stats");
+        assert_eq!(
+            containers[0],
+            ContainerResource {
+                name: "nginx".to_owned(),
+                id: "8dfafdbc3a40".to_owned(),
+                image: "nginx:1.25".to_owned(),
+                cpu_usage: None,
+                memory_used_bytes: 44482560,
+                memory_limit_bytes: Some(268435456),
+                network_rx_bytes: 16000,
+                network_tx_bytes: 5500,
+            }
+        );
+        assert_eq!(containers[1].name, "postgres");
+        assert_eq!(containers[1].id, "9cd87a1b2c3d");
+
+        let containers = collector.collect().expect("container stats");
+        assert_eq!(containers[0].cpu_usage, Some(40.0));
+        assert_eq!(containers[1].cpu_usage, Some(40.0));
+
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[test]
+    fn test_collect_with_hung_container() {
+        let socket = fake_engine("docker-hung", 3, Some("9cd87a1b2c3d"));
+        let mut collector = DockerCollector::new(&socket);
+        let start = Instant::now();
+
+        let containers = collector.collect().expect("container stats");
+        assert!(start.elapsed() >= REQUEST_TIMEOUT && start.elapsed() < COLLECT_TIMEOUT);
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].name, "nginx");
+
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[test]
+    fn test_docker_runner() {
+        let socket = fake_engine("docker-runner", 3, None);
+        let runner = DockerRunner::new(&socket);
+
+        assert_eq!(runner.results(), None);
+        runner.request();
+        runner.request();
+
+        let start = Instant::now();
+        while runner.results().is_none() && start.elapsed() < COLLECT_TIMEOUT {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let containers = runner.results().expect("container stats");
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].name, "nginx");
+
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[test]
+    fn test_collect_without_engine() {
+        let mut collector = DockerCollector::new(Path::new("/nonexistent/tum/docker.sock"));
+
+        assert!(matches!(
+            collector.collect(),
+            Err(DockerError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_cpu_usage() {
+        let stats = CpuStats {
+            cpu_usage: CpuUsage {
+                total_usage: 3_000_000_000,
+            },
+            system_cpu_usage: Some(110_000_000_000),
+            online_cpus: Some(4),
+        };
+
+        assert_eq!(cpu_usage(&stats, None), None);
+        assert_eq!(
+            cpu_usage(&stats, Some(&(2_000_000_000, 100_000_000_000))),
+            Some(40.0)
+        );
+        assert_eq!(
+            cpu_usage(&stats, Some(&(2_000_000_000, 110_000_000_000))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_http_response() {
+        assert_eq!(
+            parse_http_response(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]")
+                .expect("valid response"),
+            b"[]"
+        );
+        assert_eq!(
+            parse_http_response(
+                b"HTTP/1.1 200 OK\r\ntransfer-encoding: Chunked\r\n\r\n3\r\n[1,\r\n2;ext\r\n2]\r\n0\r\n\r\n"
+            )
+            .expect("valid response"),
+            b"[1,2]"
+        );
+        assert!(matches!(
+            parse_http_response(b"HTTP/1.1 500 Internal Server Error\r\n\r\n"),
+            Err(DockerError::Http(_))
+        ));
+        assert!(matches!(
+            parse_http_response(b"HTTP/1.1 200 OK\r\n"),
+            Err(DockerError::Http(_))
+        ));
+        assert!(matches!(
+            parse_http_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n10\r\nabc"),
+            Err(DockerError::Http(_))
+        ));
+    }
+}
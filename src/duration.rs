@@ -0,0 +1,124 @@
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+pub enum DurationError {
+    MissingValue(String),
+    UnknownUnit(String),
+    Overflow(String),
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DurationError::MissingValue(ref duration) => {
+                write!(f, "Missing numeric value in duration '{}'.", duration)
+            }
+            DurationError::UnknownUnit(ref duration) => write!(
+                f,
+                "Unknown unit in duration '{}', expected one of 'ms', 's', 'm' or 'h'.",
+                duration
+            ),
+            DurationError::Overflow(ref duration) => {
+                write!(f, "Duration '{}' is too large.", duration)
+            }
+        }
+    }
+}
+
+// NOTE: parses durations like '500ms', '2s', '1m' or '1h', a value without
+//       a unit is interpreted as seconds
+pub fn parse_duration(duration: &str) -> Result<Duration, DurationError> {
+    let trimmed = duration.trim();
+    let unit_start = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (value, unit) = trimmed.split_at(unit_start);
+
+    let value: u64 = value
+        .parse()
+        .map_err(|_| DurationError::MissingValue(duration.to_owned()))?;
+    let millis_per_unit: u64 = match unit.trim() {
+        "ms" => 1,
+        "" | "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return Err(DurationError::UnknownUnit(duration.to_owned())),
+    };
+
+    value
+        .checked_mul(millis_per_unit)
+        .map(Duration::from_millis)
+        .ok_or(DurationError::Overflow(duration.to_owned()))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDuration {
+    Seconds(u64),
+    Text(String),
+}
+
+pub fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match RawDuration::deserialize(deserializer)? {
+        RawDuration::Seconds(seconds) => Ok(Duration::from_secs(seconds)),
+        RawDuration::Text(text) => parse_duration(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+pub fn deserialize_optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<RawDuration>::deserialize(deserializer)? {
+        Some(RawDuration::Seconds(seconds)) => Ok(Some(Duration::from_secs(seconds))),
+        Some(RawDuration::Text(text)) => parse_duration(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("15"), Ok(Duration::from_secs(15)));
+        assert_eq!(parse_duration(" 250 ms "), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("0s"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_invalid_duration() {
+        assert_eq!(
+            parse_duration("ms"),
+            Err(DurationError::MissingValue("ms".to_owned()))
+        );
+        assert_eq!(
+            parse_duration("-1s"),
+            Err(DurationError::MissingValue("-1s".to_owned()))
+        );
+        assert_eq!(
+            parse_duration("1.5s"),
+            Err(DurationError::UnknownUnit("1.5s".to_owned()))
+        );
+        assert_eq!(
+            parse_duration("5d"),
+            Err(DurationError::UnknownUnit("5d".to_owned()))
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615h"),
+            Err(DurationError::Overflow("18446744073709551615h".to_owned()))
+        );
+    }
+}
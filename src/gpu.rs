@@ -0,0 +1,440 @@
+use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+use nvml_wrapper::error::NvmlError;
+use nvml_wrapper::{Device, Nvml};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const DRM_ROOT: &str = "/sys/class/drm";
+
+// NOTE: values the backend of a GPU doesn't expose are 'null', i.e. Intel GPUs
+//       report neither utilization nor memory through sysfs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct GpuResource {
+    pub id: String,
+    pub name: String,
+    pub backend: String,
+    pub utilization_percent: Option<f64>,
+    pub memory_used_bytes: Option<u64>,
+    pub memory_total_bytes: Option<u64>,
+    pub temperature_celsius: Option<f64>,
+    pub power_w: Option<f64>,
+}
+
+// NOTE: a source of GPU statistics, a backend that finds no GPU returns an
+//       empty list, so every backend can be tried on every system
+pub trait GpuBackend: fmt::Debug + Send {
+    fn collect(&mut self) -> Vec<GpuResource>;
+}
+
+// NOTE: delay before the next attempt to initialize NVML, doubled after
+//       every failed attempt, i.e. while the driver is not loaded yet
+const NVML_MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const NVML_MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Default)]
+struct Backoff {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Backoff {
+    fn is_due(&self, now: Instant) -> bool {
+        self.retry_at
+            .map(|retry_at| now >= retry_at)
+            .unwrap_or(true)
+    }
+
+    fn fail(&mut self, now: Instant) {
+        let delay = NVML_MIN_RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(self.failures))
+            .min(NVML_MAX_RETRY_DELAY);
+        self.failures = self.failures.saturating_add(1);
+        self.retry_at = Some(now + delay);
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.retry_at = None;
+    }
+}
+
+// NOTE: NVIDIA GPUs are read through NVML, which is loaded at runtime from the
+//       library installed together with the driver, so the backend builds and
+//       runs on systems without it
+#[derive(Debug, Default)]
+pub struct NvidiaBackend {
+    nvml: Option<Nvml>,
+    is_available: bool,
+    backoff: Backoff,
+}
+
+// NOTE: the values read from an NVIDIA GPU, implemented for the NVML device,
+//       and by the tests, which have no GPU to read
+trait NvidiaDevice {
+    fn name(&self) -> Result<String, NvmlError>;
+    fn utilization_percent(&self) -> Result<u32, NvmlError>;
+    fn memory_bytes(&self) -> Result<(u64, u64), NvmlError>;
+    fn temperature_celsius(&self) -> Result<u32, NvmlError>;
+    fn power_mw(&self) -> Result<u32, NvmlError>;
+}
+
+impl NvidiaDevice for Device<'_> {
+    fn name(&self) -> Result<String, NvmlError> {
+        Device::name(self)
+    }
+
+    fn utilization_percent(&self) -> Result<u32, NvmlError> {
+        self.utilization_rates().map(|utilization| utilization.gpu)
+    }
+
+    fn memory_bytes(&self) -> Result<(u64, u64), NvmlError> {
+        self.memory_info().map(|memory| (memory.used, memory.total))
+    }
+
+    fn temperature_celsius(&self) -> Result<u32, NvmlError> {
+        self.temperature(TemperatureSensor::Gpu)
+    }
+
+    fn power_mw(&self) -> Result<u32, NvmlError> {
+        self.power_usage()
+    }
+}
+
+fn read_nvidia_gpu(index: u32, device: &impl NvidiaDevice) -> GpuResource {
+    let memory = device.memory_bytes().ok();
+
+    GpuResource {
+        id: format!("nvidia{}", index),
+        name: device.name().unwrap_or("unknown".to_owned()),
+        backend: "nvidia".to_owned(),
+        utilization_percent: device
+            .utilization_percent()
+            .ok()
+            .map(|utilization| utilization as f64),
+        memory_used_bytes: memory.map(|(used, _)| used),
+        memory_total_bytes: memory.map(|(_, total)| total),
+        temperature_celsius: device
+            .temperature_celsius()
+            .ok()
+            .map(|temperature| temperature as f64),
+        // NOTE: NVML reports the power in mW
+        power_w: device
+            .power_mw()
+            .ok()
+            .map(|milliwatts| milliwatts as f64 / 1000.0),
+    }
+}
+
+// NOTE: every GPU is read on its own, so a GPU that can't be opened, i.e. one
+//       that fell off the bus, is skipped without hiding the others
+fn read_nvidia_gpus<D: NvidiaDevice>(
+    count: u32,
+    device_by_index: impl Fn(u32) -> Result<D, NvmlError>,
+) -> Vec<GpuResource> {
+    (0..count)
+        .filter_map(|index| match device_by_index(index) {
+            Ok(device) => Some(read_nvidia_gpu(index, &device)),
+            Err(err) => {
+                eprintln!("WRN: failed to read NVIDIA GPU {}: {}", index, err);
+                None
+            }
+        })
+        .collect()
+}
+
+impl NvidiaBackend {
+    pub fn new() -> NvidiaBackend {
+        NvidiaBackend {
+            is_available: true,
+            ..Default::default()
+        }
+    }
+
+    fn init(&mut self, now: Instant) -> Option<&Nvml> {
+        if self.nvml.is_none() && self.is_available && self.backoff.is_due(now) {
+            match Nvml::init() {
+                Ok(nvml) => {
+                    self.backoff.reset();
+                    self.nvml = Some(nvml);
+                }
+                // NOTE: the library is missing, there is no NVIDIA driver
+                Err(NvmlError::LibloadingError(_)) | Err(NvmlError::LibraryNotFound) => {
+                    self.is_available = false
+                }
+                Err(_) => self.backoff.fail(now),
+            }
+        }
+
+        self.nvml.as_ref()
+    }
+}
+
+impl GpuBackend for NvidiaBackend {
+    // NOTE: the backend is disabled when the NVML library is missing, and NVML
+    //       is initialized again, with a growing delay, when it fails to
+    //       report the number of GPUs
+    fn collect(&mut self) -> Vec<GpuResource> {
+        let now = Instant::now();
+        let Some(nvml) = self.init(now) else {
+            return Vec::new();
+        };
+
+        match nvml.device_count() {
+            Ok(count) => read_nvidia_gpus(count, |index| nvml.device_by_index(index)),
+            Err(_) => {
+                self.nvml = None;
+                self.backoff.fail(now);
+                Vec::new()
+            }
+        }
+    }
+}
+
+// NOTE: AMD and Intel GPUs are read from the DRM devices in sysfs, NVIDIA GPUs
+//       are left to the 'nvidia' backend, as their driver exposes nothing there
+#[derive(Debug)]
+pub struct DrmBackend {
+    root: PathBuf,
+}
+
+impl Default for DrmBackend {
+    fn default() -> DrmBackend {
+        DrmBackend::new(Path::new(DRM_ROOT))
+    }
+}
+
+fn vendor_name(vendor: &str) -> Option<&'static str> {
+    match vendor {
+        "0x1002" => Some("AMD"),
+        "0x8086" => Some("Intel"),
+        _ => None,
+    }
+}
+
+// NOTE: reads the first value of the given hwmon attribute, i.e. 'temp1_input'
+//       of the 'hwmon/hwmonX' directory of the device
+fn read_hwmon_value(device: &Path, attributes: &[&str]) -> Option<f64> {
+    let mut directories: Vec<PathBuf> = std::fs::read_dir(device.join("hwmon"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    directories.sort();
+
+    directories.iter().find_map(|directory| {
+        attributes.iter().find_map(|attribute| {
+            std::fs::read_to_string(directory.join(attribute))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        })
+    })
+}
+
+fn read_card(card: &str, device: &Path) -> Option<GpuResource> {
+    let read = |file: &str| {
+        std::fs::read_to_string(device.join(file))
+            .ok()
+            .map(|content| content.trim().to_owned())
+    };
+    let vendor = vendor_name(&read("vendor")?)?;
+
+    Some(GpuResource {
+        id: card.to_owned(),
+        name: format!("{} {}", vendor, read("device").unwrap_or_default()),
+        backend: "drm".to_owned(),
+        utilization_percent: read("gpu_busy_percent").and_then(|value| value.parse().ok()),
+        memory_used_bytes: read("mem_info_vram_used").and_then(|value| value.parse().ok()),
+        memory_total_bytes: read("mem_info_vram_total").and_then(|value| value.parse().ok()),
+        temperature_celsius: read_hwmon_value(device, &["temp1_input"])
+            .map(|millidegrees| millidegrees / 1000.0),
+        power_w: read_hwmon_value(device, &["power1_average", "power1_input"])
+            .map(|microwatts| microwatts / 1_000_000.0),
+    })
+}
+
+impl DrmBackend {
+    pub fn new(root: &Path) -> DrmBackend {
+        DrmBackend {
+            root: root.to_owned(),
+        }
+    }
+}
+
+impl GpuBackend for DrmBackend {
+    // NOTE: only the 'cardN' entries are devices, the 'cardN-<connector>'
+    //       entries are the display outputs of a card
+    fn collect(&mut self) -> Vec<GpuResource> {
+        let mut cards: Vec<String> = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| {
+                    name.strip_prefix("card")
+                        .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        cards.sort_by_key(|card| card[4..].parse::<u32>().unwrap_or(u32::MAX));
+
+        cards
+            .iter()
+            .filter_map(|card| read_card(card, &self.root.join(card).join("device")))
+            .collect()
+    }
+}
+
+pub fn default_gpu_backends() -> Vec<Box<dyn GpuBackend>> {
+    let mut backends: Vec<Box<dyn GpuBackend>> = vec![Box::new(NvidiaBackend::new())];
+    if cfg!(target_os = "linux") {
+        backends.push(Box::new(DrmBackend::default()));
+    }
+
+    backends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sys/class/drm");
+
+    #[test]
+    fn test_drm_backend() {
+        let mut backend = DrmBackend::new(Path::new(FIXTURES));
+
+        assert_eq!(
+            backend.collect(),
+            vec![
+                GpuResource {
+                    id: "card0".to_owned(),
+                    name: "Intel 0x9a49".to_owned(),
+                    backend: "drm".to_owned(),
+                    utilization_percent: None,
+                    memory_used_bytes: None,
+                    memory_total_bytes: None,
+                    temperature_celsius: None,
+                    power_w: None,
+                },
+                GpuResource {
+                    id: "card1".to_owned(),
+                    name: "AMD 0x73bf".to_owned(),
+                    backend: "drm".to_owned(),
+                    utilization_percent: Some(37.0),
+                    memory_used_bytes: Some(2147483648),
+                    memory_total_bytes: Some(17163091968),
+                    temperature_celsius: Some(54.0),
+                    power_w: Some(112.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drm_backend_without_gpu() {
+        let mut backend = DrmBackend::new(&Path::new(FIXTURES).join("missing"));
+
+        assert!(backend.collect().is_empty());
+    }
+
+    #[derive(Default)]
+    struct FakeDevice {
+        name: Option<&'static str>,
+        utilization_percent: Option<u32>,
+        memory_bytes: Option<(u64, u64)>,
+        temperature_celsius: Option<u32>,
+        power_mw: Option<u32>,
+    }
+
+    impl NvidiaDevice for FakeDevice {
+        fn name(&self) -> Result<String, NvmlError> {
+            self.name
+                .map(|name| name.to_owned())
+                .ok_or(NvmlError::NotSupported)
+        }
+
+        fn utilization_percent(&self) -> Result<u32, NvmlError> {
+            self.utilization_percent.ok_or(NvmlError::NotSupported)
+        }
+
+        fn memory_bytes(&self) -> Result<(u64, u64), NvmlError> {
+            self.memory_bytes.ok_or(NvmlError::NotSupported)
+        }
+
+        fn temperature_celsius(&self) -> Result<u32, NvmlError> {
+            self.temperature_celsius.ok_or(NvmlError::NotSupported)
+        }
+
+        fn power_mw(&self) -> Result<u32, NvmlError> {
+            self.power_mw.ok_or(NvmlError::NotSupported)
+        }
+    }
+
+    #[test]
+    fn test_read_nvidia_gpus() {
+        let gpus = read_nvidia_gpus(3, |index| match index {
+            0 => Ok(FakeDevice {
+                name: Some("NVIDIA GeForce RTX 3080, Founders Edition"),
+                utilization_percent: Some(42),
+                memory_bytes: Some((1073741824, 10737418240)),
+                temperature_celsius: Some(61),
+                power_mw: Some(215500),
+            }),
+            1 => Err(NvmlError::GpuLost),
+            _ => Ok(FakeDevice::default()),
+        });
+
+        assert_eq!(
+            gpus,
+            vec![
+                GpuResource {
+                    id: "nvidia0".to_owned(),
+                    name: "NVIDIA GeForce RTX 3080, Founders Edition".to_owned(),
+                    backend: "nvidia".to_owned(),
+                    utilization_percent: Some(42.0),
+                    memory_used_bytes: Some(1073741824),
+                    memory_total_bytes: Some(10737418240),
+                    temperature_celsius: Some(61.0),
+                    power_w: Some(215.5),
+                },
+                GpuResource {
+                    id: "nvidia2".to_owned(),
+                    name: "unknown".to_owned(),
+                    backend: "nvidia".to_owned(),
+                    utilization_percent: None,
+                    memory_used_bytes: None,
+                    memory_total_bytes: None,
+                    temperature_celsius: None,
+                    power_w: None,
+                },
+            ]
+        );
+        assert!(read_nvidia_gpus(0, |_| Ok(FakeDevice::default())).is_empty());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let start = Instant::now();
+        let mut backoff = Backoff::default();
+        assert!(backoff.is_due(start));
+
+        backoff.fail(start);
+        assert!(!backoff.is_due(start));
+        assert!(backoff.is_due(start + Duration::from_secs(1)));
+
+        backoff.fail(start);
+        assert!(!backoff.is_due(start + Duration::from_secs(1)));
+        assert!(backoff.is_due(start + Duration::from_secs(2)));
+
+        (0..20).for_each(|_| backoff.fail(start));
+        assert!(!backoff.is_due(start + Duration::from_secs(299)));
+        assert!(backoff.is_due(start + NVML_MAX_RETRY_DELAY));
+
+        backoff.reset();
+        assert!(backoff.is_due(start));
+    }
+}
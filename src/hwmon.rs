@@ -0,0 +1,128 @@
+use std::path::Path;
+
+// NOTE: fan speeds in RPM and voltages in V, labeled with the name of the
+//       hwmon device followed by the label of the input, or the name of the
+//       input if it has no label, i.e. 'nct6775 CPU Fan' or 'nct6775 in1'
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct HwmonSensors {
+    pub fans: Vec<(String, u64)>,
+    pub voltages: Vec<(String, f64)>,
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_owned())
+}
+
+// NOTE: returns the label and the raw value of every '<prefix>N_input' file
+//       of a hwmon device, sorted by the input name
+fn read_inputs(device: &Path, name: &str, prefix: &str) -> Vec<(String, i64)> {
+    let entries = match std::fs::read_dir(device) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut inputs: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|file_name| {
+            let input = file_name.strip_suffix("_input")?;
+            let index = input.strip_prefix(prefix)?;
+            match !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()) {
+                true => Some(input.to_owned()),
+                false => None,
+            }
+        })
+        .collect();
+    inputs.sort();
+
+    inputs
+        .into_iter()
+        .filter_map(|input| {
+            let value = read_trimmed(&device.join(format!("{}_input", input)))?
+                .parse()
+                .ok()?;
+            let label = read_trimmed(&device.join(format!("{}_label", input)))
+                .filter(|label| !label.is_empty())
+                .unwrap_or(input);
+            Some((format!("{} {}", name, label), value))
+        })
+        .collect()
+}
+
+fn read_hwmon_from(directory: &Path) -> HwmonSensors {
+    let mut sensors = HwmonSensors::default();
+    let mut devices: Vec<_> = match std::fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect(),
+        Err(_) => return sensors,
+    };
+    devices.sort();
+
+    for device in devices {
+        let name = match read_trimmed(&device.join("name")) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        sensors.fans.extend(
+            read_inputs(&device, &name, "fan")
+                .into_iter()
+                .map(|(label, rpm)| (label, rpm.max(0) as u64)),
+        );
+        // NOTE: voltages are reported in mV
+        sensors.voltages.extend(
+            read_inputs(&device, &name, "in")
+                .into_iter()
+                .map(|(label, millivolts)| (label, millivolts as f64 / 1000.0)),
+        );
+    }
+
+    sensors
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_hwmon() -> HwmonSensors {
+    read_hwmon_from(Path::new("/sys/class/hwmon"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_hwmon() -> HwmonSensors {
+    HwmonSensors::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/sys/class/hwmon"
+    );
+
+    #[test]
+    fn test_read_hwmon() {
+        assert_eq!(
+            read_hwmon_from(Path::new(FIXTURES)),
+            HwmonSensors {
+                fans: vec![
+                    ("nct6775 CPU Fan".to_owned(), 1250),
+                    ("nct6775 fan2".to_owned(), 830)
+                ],
+                voltages: vec![
+                    ("nct6775 Vcore".to_owned(), 1.04),
+                    ("nct6775 in1".to_owned(), 3.312)
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_missing_hwmon() {
+        assert_eq!(
+            read_hwmon_from(&Path::new(FIXTURES).join("missing")),
+            HwmonSensors::default()
+        );
+    }
+}
@@ -3,11 +3,31 @@
 //! Can contain information about what the binary does, command-line options,
 //! configuration, etc.
 
+mod aggregation;
+mod alert;
+mod cgroup;
 mod command;
 mod configuration;
+mod deadband;
+mod docker;
+mod duration;
+mod gpu;
+mod hwmon;
+mod meminfo;
 mod monitor;
 mod mqtt_client;
+mod network;
+mod pattern;
+mod payload;
+mod power;
+mod pressure;
+mod probe;
+mod proc_stat;
+mod process;
 mod resource;
+mod scheduler;
+mod sockets;
+mod systemd;
 // ... other modules
 
 // This is the only export from the crate. It is marked hidden and
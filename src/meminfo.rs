@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+// NOTE: memory figures that are reported only by '/proc/meminfo' on Linux,
+//       all of them are in bytes
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct MemInfo {
+    pub buffers: Option<u64>,
+    pub cached: Option<u64>,
+    pub shared: Option<u64>,
+    pub slab: Option<u64>,
+}
+
+// NOTE: returns every field of '/proc/meminfo', values with the 'kB' unit are
+//       converted to bytes, values without a unit, like 'HugePages_Total',
+//       are returned as they are
+fn parse_fields(content: &str) -> HashMap<&str, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let mut value = value.split_whitespace();
+            let number: u64 = value.next()?.parse().ok()?;
+            match value.next() {
+                Some("kB") => Some((name.trim(), number.saturating_mul(1024))),
+                None => Some((name.trim(), number)),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+pub fn parse_meminfo(content: &str) -> MemInfo {
+    let fields = parse_fields(content);
+
+    MemInfo {
+        buffers: fields.get("Buffers").copied(),
+        cached: fields.get("Cached").copied(),
+        shared: fields.get("Shmem").copied(),
+        slab: fields.get("Slab").copied(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_meminfo() -> MemInfo {
+    match std::fs::read_to_string("/proc/meminfo") {
+        Ok(content) => parse_meminfo(&content),
+        Err(_) => MemInfo::default(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_meminfo() -> MemInfo {
+    MemInfo::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = include_str!("../tests/fixtures/proc/meminfo");
+
+    #[test]
+    fn test_parse_meminfo() {
+        assert_eq!(
+            parse_meminfo(MEMINFO),
+            MemInfo {
+                buffers: Some(523004 * 1024),
+                cached: Some(7861972 * 1024),
+                shared: Some(418932 * 1024),
+                slab: Some(612344 * 1024),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = parse_fields(MEMINFO);
+
+        assert_eq!(fields.get("MemTotal"), Some(&(16318576 * 1024)));
+        assert_eq!(fields.get("HugePages_Total"), Some(&0));
+        assert_eq!(fields.get("Active(anon)"), Some(&(4021176 * 1024)));
+    }
+
+    #[test]
+    fn test_parse_partial_meminfo() {
+        assert_eq!(
+            parse_meminfo("MemTotal: 1024 kB\nBuffers: 2 kB\nCached: invalid kB\n"),
+            MemInfo {
+                buffers: Some(2048),
+                ..Default::default()
+            }
+        );
+    }
+}
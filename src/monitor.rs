@@ -1,18 +1,28 @@
+use crate::aggregation::Aggregator;
+use crate::alert::{AlertEngine, AlertRule};
+use crate::configuration::{AggregationConfiguration, Configuration, PublishMode};
+use crate::deadband::{ChangeFilter, Deadband};
 use crate::mqtt_client::{MqttClient, MqttClientError};
-use crate::resource::Resource;
+use crate::payload::{build_messages, select_groups};
+use crate::resource::{Resource, ResourceGroup};
+use crate::scheduler::Schedule;
 use gethostname::gethostname;
+use serde_json::{Map, Value};
 use std::any::Any;
 use std::boxed::Box;
 use std::fmt;
 use std::marker::Send;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug)]
 pub enum ResourceMonitorError {
     ResourceSynchronization,
     TransmissionSynchronization,
     ExecutionSynchronization,
+    ExecutionPanic,
+    MonitoringRate(ResourceGroup),
     StringConversion,
     Serialization,
     Transmission(MqttClientError),
@@ -30,6 +40,16 @@ impl fmt::Display for ResourceMonitorError {
             ResourceMonitorError::ExecutionSynchronization => {
                 write!(f, "Synchronization issues for 'execution' attribute.")
             }
+            ResourceMonitorError::ExecutionPanic => {
+                write!(f, "Monitoring thread panicked.")
+            }
+            ResourceMonitorError::MonitoringRate(group) => {
+                write!(
+                    f,
+                    "Monitoring rate of '{}' group is not set or is 0.",
+                    group.name()
+                )
+            }
             ResourceMonitorError::StringConversion => {
                 write!(f, "Failed to convert data to string.")
             }
@@ -48,57 +68,251 @@ impl From<MqttClientError> for ResourceMonitorError {
 }
 
 impl From<Box<dyn Any + Send>> for ResourceMonitorError {
-    fn from(item: Box<dyn Any + Send>) -> ResourceMonitorError {
-        item.into()
+    fn from(_: Box<dyn Any + Send>) -> ResourceMonitorError {
+        ResourceMonitorError::ExecutionPanic
+    }
+}
+
+// NOTE: a message the client fails to publish, like one with a topic the
+//       broker rejects, is skipped, so it doesn't stop the monitoring
+fn publish_or_skip(transmitter: &mut MqttClient, topic: &String, data: &[u8]) {
+    if let Err(err) = transmitter.publish(topic, data) {
+        eprintln!("WRN: failed to publish to '{}': {}", topic, err);
+    }
+}
+
+struct GroupSettings {
+    group: ResourceGroup,
+    publish_rate: Duration,
+    deadband: Option<Deadband>,
+    max_silence: Option<Duration>,
+    aggregation: Option<AggregationConfiguration>,
+}
+
+struct GroupState {
+    group: ResourceGroup,
+    publish_schedule: Schedule,
+    sample_schedule: Option<Schedule>,
+    aggregator: Option<Aggregator>,
+    change_filter: ChangeFilter,
+}
+
+fn advance_schedule(schedule: &mut Schedule, group: ResourceGroup, now: Instant) {
+    let missed_ticks = schedule.advance(now);
+    if missed_ticks > 0 {
+        eprintln!(
+            "WRN: missed {} sampling tick(s) of '{}' group",
+            missed_ticks,
+            group.name()
+        );
+    }
+}
+
+impl GroupState {
+    fn new(
+        settings: &GroupSettings,
+        now: Instant,
+        wall_clock: Duration,
+        align: bool,
+    ) -> GroupState {
+        GroupState {
+            group: settings.group,
+            publish_schedule: Schedule::new(settings.publish_rate, now, wall_clock, align),
+            sample_schedule: settings.aggregation.as_ref().map(|aggregation| {
+                Schedule::new(aggregation.sampling_rate, now, wall_clock, align)
+            }),
+            aggregator: settings
+                .aggregation
+                .as_ref()
+                .map(|aggregation| Aggregator::new(&aggregation.statistics)),
+            change_filter: ChangeFilter::new(settings.deadband, settings.max_silence),
+        }
+    }
+
+    fn next_tick(&self) -> Instant {
+        match self.sample_schedule {
+            Some(ref sample_schedule) => self
+                .publish_schedule
+                .next_tick()
+                .min(sample_schedule.next_tick()),
+            None => self.publish_schedule.next_tick(),
+        }
+    }
+
+    // NOTE: returns whether the group needs to be sampled and published at 'now',
+    //       groups without aggregation are sampled only when they are published
+    fn take_due(&mut self, now: Instant) -> (bool, bool) {
+        let is_publish_due = self.publish_schedule.is_due(now);
+        if is_publish_due {
+            advance_schedule(&mut self.publish_schedule, self.group, now);
+        }
+
+        let is_sample_due = match self.sample_schedule {
+            Some(ref mut sample_schedule) if sample_schedule.is_due(now) => {
+                advance_schedule(sample_schedule, self.group, now);
+                true
+            }
+            Some(_) => false,
+            None => is_publish_due,
+        };
+
+        (is_sample_due, is_publish_due)
+    }
+
+    fn add_sample(&mut self, stats: &Value) {
+        if let Some(ref mut aggregator) = self.aggregator {
+            aggregator.add(select_groups(stats.clone(), &[self.group]));
+        }
+    }
+
+    fn take_publishable(&mut self, stats: &Value, now: Instant) -> Option<Value> {
+        let group_stats = match self.aggregator {
+            Some(ref mut aggregator) => aggregator.aggregate()?,
+            None => select_groups(stats.clone(), &[self.group]),
+        };
+
+        match self.change_filter.should_publish(&group_stats, now) {
+            true => Some(group_stats),
+            false => None,
+        }
     }
 }
 
 pub struct ResourceMonitor {
-    sampling_rate_s: u64,
+    groups: Vec<GroupSettings>,
+    publish_mode: PublishMode,
+    align_to_wall_clock: bool,
+    alert_rules: Vec<AlertRule>,
     resource: Arc<Mutex<Resource>>,
     transmitter: Arc<Mutex<MqttClient>>,
     stop_monitoring: Arc<Mutex<bool>>,
 }
 
 impl ResourceMonitor {
-    pub fn new(sampling_rate_s: u64, transmitter: MqttClient) -> ResourceMonitor {
-        ResourceMonitor {
-            sampling_rate_s: if sampling_rate_s > 0 {
-                sampling_rate_s
-            } else {
-                5
-            },
-            resource: Arc::new(Mutex::new(Resource::new())),
+    // NOTE: a zero monitoring rate would make the scheduler spin, so it is
+    //       rejected here as well, for configurations that were not validated
+    pub fn new(
+        configuration: &Configuration,
+        transmitter: MqttClient,
+    ) -> Result<ResourceMonitor, ResourceMonitorError> {
+        let groups = ResourceGroup::ALL
+            .iter()
+            .filter(|group| configuration.is_group_enabled(**group))
+            .map(|group| {
+                let group_configuration = configuration.groups.get(group);
+                Ok(GroupSettings {
+                    group: *group,
+                    publish_rate: configuration
+                        .group_monitoring_rate(*group)
+                        .ok_or(ResourceMonitorError::MonitoringRate(*group))?,
+                    deadband: group_configuration.and_then(|config| config.deadband),
+                    max_silence: group_configuration.and_then(|config| config.max_silence),
+                    aggregation: group_configuration.and_then(|config| config.aggregation.clone()),
+                })
+            })
+            .collect::<Result<Vec<GroupSettings>, ResourceMonitorError>>()?;
+
+        Ok(ResourceMonitor {
+            groups,
+            publish_mode: configuration.publish_mode,
+            align_to_wall_clock: configuration.align_to_wall_clock,
+            alert_rules: configuration.alerts.clone(),
+            resource: Arc::new(Mutex::new(Resource::new(configuration))),
             transmitter: Arc::new(Mutex::new(transmitter)),
             stop_monitoring: Arc::new(Mutex::new(false)),
-        }
+        })
     }
 
     pub fn start(&self) -> Result<(), ResourceMonitorError> {
         let resource = Arc::clone(&self.resource);
         let transmitter = Arc::clone(&self.transmitter);
         let stop_monitoring = Arc::clone(&self.stop_monitoring);
-        let sampling_rate_s = self.sampling_rate_s;
+        let publish_mode = self.publish_mode;
+        let start_time = Instant::now();
+        let wall_clock = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::new(0, 0));
+        let mut groups: Vec<GroupState> = self
+            .groups
+            .iter()
+            .map(|settings| {
+                GroupState::new(settings, start_time, wall_clock, self.align_to_wall_clock)
+            })
+            .collect();
+        let mut alert_engine = AlertEngine::new(self.alert_rules.clone());
 
         let handler = thread::spawn(move || -> Result<(), ResourceMonitorError> {
             loop {
+                let now = Instant::now();
+                let mut sampled_groups = Vec::new();
+                let mut published_groups = Vec::new();
+                groups.iter_mut().for_each(|state| {
+                    let (is_sample_due, is_publish_due) = state.take_due(now);
+                    if is_sample_due {
+                        sampled_groups.push(state.group);
+                    }
+                    if is_publish_due {
+                        published_groups.push(state.group);
+                    }
+                });
+
                 match resource.lock() {
-                    Ok(mut res) => res.refresh(),
+                    Ok(mut res) => res.refresh(&sampled_groups),
                     Err(_) => return Err(ResourceMonitorError::ResourceSynchronization),
                 }
 
-                let topic = match gethostname().to_str() {
-                    Some(hostname) => format!("device/{}/system/stats", hostname),
+                let base_topic = match gethostname().to_str() {
+                    Some(hostname) => format!("device/{}/system", hostname),
                     None => return Err(ResourceMonitorError::StringConversion),
                 };
 
-                let data = match serde_json::to_vec(resource.as_ref()) {
-                    Ok(data) => data,
+                let stats = match serde_json::to_value(resource.as_ref()) {
+                    Ok(stats) => stats,
                     Err(_) => return Err(ResourceMonitorError::Serialization),
                 };
 
+                let mut publish_stats = Map::new();
+                for state in groups.iter_mut() {
+                    if sampled_groups.contains(&state.group) {
+                        state.add_sample(&stats);
+                    }
+                    if published_groups.contains(&state.group) {
+                        if let Some(Value::Object(group_stats)) =
+                            state.take_publishable(&stats, now)
+                        {
+                            publish_stats.extend(group_stats);
+                        }
+                    }
+                }
+
+                let alert_events = match sampled_groups.is_empty() || alert_engine.is_empty() {
+                    true => Vec::new(),
+                    false => {
+                        alert_engine.evaluate(&select_groups(stats.clone(), &sampled_groups), now)
+                    }
+                };
+
                 match transmitter.lock() {
-                    Ok(mut tx) => tx.publish(&topic, data.as_slice())?,
+                    Ok(mut tx) => {
+                        for event in alert_events {
+                            let data = match serde_json::to_vec(&event) {
+                                Ok(data) => data,
+                                Err(_) => return Err(ResourceMonitorError::Serialization),
+                            };
+                            publish_or_skip(&mut tx, &format!("{}/alerts", base_topic), &data);
+                        }
+                    }
+                    Err(_) => return Err(ResourceMonitorError::TransmissionSynchronization),
+                };
+
+                match transmitter.lock() {
+                    Ok(mut tx) if !publish_stats.is_empty() => {
+                        let publish_stats = Value::Object(publish_stats);
+                        for message in build_messages(&base_topic, publish_mode, &publish_stats) {
+                            publish_or_skip(&mut tx, &message.topic, &message.payload);
+                        }
+                    }
+                    Ok(_) => (),
                     Err(_) => return Err(ResourceMonitorError::TransmissionSynchronization),
                 };
 
@@ -111,7 +325,12 @@ impl ResourceMonitor {
                     Err(_) => return Err(ResourceMonitorError::ExecutionSynchronization),
                 }
 
-                thread::sleep(std::time::Duration::from_secs(sampling_rate_s));
+                let next_tick = groups
+                    .iter()
+                    .map(|state| state.next_tick())
+                    .min()
+                    .unwrap_or(now);
+                thread::sleep(next_tick.saturating_duration_since(Instant::now()));
             }
             Ok(())
         });
@@ -8,7 +8,7 @@ use std::time::Duration;
 #[derive(Debug)]
 pub enum MqttClientError {
     Client(ClientError),
-    Connection(ConnectionError),
+    Connection(Box<ConnectionError>),
 }
 
 impl fmt::Display for MqttClientError {
@@ -32,7 +32,7 @@ impl From<ClientError> for MqttClientError {
 
 impl From<ConnectionError> for MqttClientError {
     fn from(item: ConnectionError) -> MqttClientError {
-        MqttClientError::Connection(item)
+        MqttClientError::Connection(Box::new(item))
     }
 }
 
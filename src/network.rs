@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+// NOTE: cumulative counters of a network interface since it was brought up
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct NetworkCounters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+impl NetworkCounters {
+    // NOTE: counters only grow while an interface is up, so a counter lower
+    //       than in the previous sample means that the interface was
+    //       restarted, or that the counter wrapped around
+    pub fn is_reset_since(&self, previous: &NetworkCounters) -> bool {
+        self.rx_bytes < previous.rx_bytes
+            || self.tx_bytes < previous.tx_bytes
+            || self.rx_packets < previous.rx_packets
+            || self.tx_packets < previous.tx_packets
+            || self.rx_errors < previous.rx_errors
+            || self.tx_errors < previous.tx_errors
+            || self.rx_dropped < previous.rx_dropped
+            || self.tx_dropped < previous.tx_dropped
+    }
+}
+
+// NOTE: attributes of a network interface, the addresses are reported in the
+//       order the system reports them, 'link_speed_mbps' is reported only for
+//       interfaces with a negotiated link
+#[derive(Debug, PartialEq, Default, Clone, Deserialize, Serialize)]
+pub struct InterfaceInfo {
+    pub mac_address: Option<String>,
+    pub ipv4_addresses: Vec<String>,
+    pub ipv6_addresses: Vec<String>,
+    pub mtu: Option<u32>,
+    pub operstate: Option<String>,
+    pub link_speed_mbps: Option<u64>,
+}
+
+impl InterfaceInfo {
+    pub fn set_addresses(&mut self, addresses: InterfaceAddresses) {
+        self.ipv4_addresses = addresses.ipv4;
+        self.ipv6_addresses = addresses.ipv6;
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct InterfaceAddresses {
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+}
+
+// NOTE: source of the network interface counters and attributes, so the
+//       computation of the network resource can be tested with recorded values
+pub trait NetworkSource: fmt::Debug + Send {
+    fn counters(&mut self) -> HashMap<String, NetworkCounters>;
+
+    // NOTE: the attributes without the addresses, which are read separately
+    fn interface_info(&mut self, interface: &str) -> InterfaceInfo;
+
+    // NOTE: addresses of every interface, keyed by the interface name, read
+    //       on every sample, as they can change while the link stays up
+    fn addresses(&mut self) -> HashMap<String, InterfaceAddresses>;
+
+    // NOTE: cheap to read, used to detect when the attributes of an interface
+    //       have to be read again
+    fn operstate(&mut self, interface: &str) -> Option<String>;
+}
+
+// NOTE: returns the counters of every interface in '/proc/net/dev', the first
+//       two lines of the file are headers
+pub fn parse_proc_net_dev(content: &str) -> HashMap<String, NetworkCounters> {
+    content
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, fields) = line.split_once(':')?;
+            let values: Vec<u64> = fields
+                .split_whitespace()
+                .map(|field| field.parse().ok())
+                .collect::<Option<Vec<u64>>>()?;
+            if values.len() < 16 {
+                return None;
+            }
+
+            Some((
+                interface.trim().to_owned(),
+                NetworkCounters {
+                    rx_bytes: values[0],
+                    rx_packets: values[1],
+                    rx_errors: values[2],
+                    rx_dropped: values[3],
+                    tx_bytes: values[8],
+                    tx_packets: values[9],
+                    tx_errors: values[10],
+                    tx_dropped: values[11],
+                },
+            ))
+        })
+        .collect()
+}
+
+// NOTE: the MAC address is 'None' for interfaces without one, like loopback,
+//       and the link speed for interfaces without a link, where the kernel
+//       reports '-1' or fails to read the 'speed' file
+pub fn read_interface_info_from(directory: &Path) -> InterfaceInfo {
+    let read = |file: &str| {
+        std::fs::read_to_string(directory.join(file))
+            .ok()
+            .map(|content| content.trim().to_owned())
+    };
+
+    InterfaceInfo {
+        mac_address: read("address")
+            .filter(|address| !address.is_empty() && address != "00:00:00:00:00:00"),
+        ipv4_addresses: Vec::new(),
+        ipv6_addresses: Vec::new(),
+        mtu: read("mtu").and_then(|mtu| mtu.parse().ok()),
+        operstate: read("operstate"),
+        link_speed_mbps: read("speed").and_then(|speed| speed.parse().ok()),
+    }
+}
+
+// NOTE: all addresses are read with a single 'getifaddrs' call
+#[cfg(unix)]
+fn read_addresses() -> HashMap<String, InterfaceAddresses> {
+    use std::ffi::CStr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut addresses: HashMap<String, InterfaceAddresses> = HashMap::new();
+    let mut interfaces: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: the list is only read until it is freed with 'freeifaddrs', and
+    //         every address is read as the type its family specifies
+    unsafe {
+        if libc::getifaddrs(&mut interfaces) != 0 {
+            return addresses;
+        }
+
+        let mut interface = interfaces;
+        while let Some(entry) = interface.as_ref() {
+            interface = entry.ifa_next;
+            if entry.ifa_addr.is_null() || entry.ifa_name.is_null() {
+                continue;
+            }
+
+            let name = CStr::from_ptr(entry.ifa_name)
+                .to_string_lossy()
+                .into_owned();
+            match i32::from((*entry.ifa_addr).sa_family) {
+                libc::AF_INET => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in);
+                    let address = Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr));
+                    addresses
+                        .entry(name)
+                        .or_default()
+                        .ipv4
+                        .push(address.to_string());
+                }
+                libc::AF_INET6 => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in6);
+                    let address = Ipv6Addr::from(address.sin6_addr.s6_addr);
+                    addresses
+                        .entry(name)
+                        .or_default()
+                        .ipv6
+                        .push(address.to_string());
+                }
+                _ => (),
+            }
+        }
+
+        libc::freeifaddrs(interfaces);
+    }
+
+    addresses
+}
+
+#[cfg(not(unix))]
+fn read_addresses() -> HashMap<String, InterfaceAddresses> {
+    HashMap::new()
+}
+
+// NOTE: reads '/proc/net/dev' on Linux, other platforms use 'sysinfo', which
+//       doesn't report dropped packets
+#[derive(Debug, Default)]
+pub struct SystemNetworkSource {
+    #[cfg(not(target_os = "linux"))]
+    system: sysinfo::System,
+}
+
+#[cfg(target_os = "linux")]
+impl NetworkSource for SystemNetworkSource {
+    fn counters(&mut self) -> HashMap<String, NetworkCounters> {
+        match std::fs::read_to_string("/proc/net/dev") {
+            Ok(content) => parse_proc_net_dev(&content),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    fn interface_info(&mut self, interface: &str) -> InterfaceInfo {
+        read_interface_info_from(&Path::new(SYS_CLASS_NET).join(interface))
+    }
+
+    fn addresses(&mut self) -> HashMap<String, InterfaceAddresses> {
+        read_addresses()
+    }
+
+    fn operstate(&mut self, interface: &str) -> Option<String> {
+        std::fs::read_to_string(Path::new(SYS_CLASS_NET).join(interface).join("operstate"))
+            .ok()
+            .map(|operstate| operstate.trim().to_owned())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl NetworkSource for SystemNetworkSource {
+    fn counters(&mut self) -> HashMap<String, NetworkCounters> {
+        use sysinfo::{NetworkExt, SystemExt};
+
+        self.system.refresh_networks_list();
+        self.system
+            .networks()
+            .into_iter()
+            .map(|(interface, data)| {
+                (
+                    interface.to_owned(),
+                    NetworkCounters {
+                        rx_bytes: data.total_received(),
+                        tx_bytes: data.total_transmitted(),
+                        rx_packets: data.total_packets_received(),
+                        tx_packets: data.total_packets_transmitted(),
+                        rx_errors: data.total_errors_on_received(),
+                        tx_errors: data.total_errors_on_transmitted(),
+                        rx_dropped: 0,
+                        tx_dropped: 0,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn interface_info(&mut self, interface: &str) -> InterfaceInfo {
+        use sysinfo::{NetworkExt, SystemExt};
+
+        let mac_address = self
+            .system
+            .networks()
+            .into_iter()
+            .find(|(name, _)| name.as_str() == interface)
+            .map(|(_, data)| data.mac_address())
+            .filter(|address| !address.is_unspecified())
+            .map(|address| address.to_string());
+
+        InterfaceInfo {
+            mac_address,
+            ..Default::default()
+        }
+    }
+
+    fn addresses(&mut self) -> HashMap<String, InterfaceAddresses> {
+        read_addresses()
+    }
+
+    fn operstate(&mut self, _interface: &str) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_dev() {
+        let counters = parse_proc_net_dev(include_str!("../tests/fixtures/proc/net/dev"));
+
+        assert_eq!(counters.len(), 3);
+        assert_eq!(
+            counters["eth0"],
+            NetworkCounters {
+                rx_bytes: 1879364512,
+                tx_bytes: 259481736,
+                rx_packets: 1524587,
+                tx_packets: 987456,
+                rx_errors: 12,
+                tx_errors: 3,
+                rx_dropped: 37,
+                tx_dropped: 5,
+            }
+        );
+        assert_eq!(counters["wlan0"], NetworkCounters::default());
+    }
+
+    #[test]
+    fn test_read_interface_info() {
+        let fixtures = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/sys/class/net"
+        ));
+
+        assert_eq!(
+            read_interface_info_from(&fixtures.join("eth0")),
+            InterfaceInfo {
+                mac_address: Some("52:54:00:12:34:56".to_owned()),
+                ipv4_addresses: Vec::new(),
+                ipv6_addresses: Vec::new(),
+                mtu: Some(1500),
+                operstate: Some("up".to_owned()),
+                link_speed_mbps: Some(1000),
+            }
+        );
+        assert_eq!(
+            read_interface_info_from(&fixtures.join("wlan0")),
+            InterfaceInfo {
+                mac_address: Some("a4:c3:f0:85:ac:2d".to_owned()),
+                ipv4_addresses: Vec::new(),
+                ipv6_addresses: Vec::new(),
+                mtu: Some(1500),
+                operstate: Some("down".to_owned()),
+                link_speed_mbps: None,
+            }
+        );
+        assert_eq!(
+            read_interface_info_from(&fixtures.join("lo")),
+            InterfaceInfo {
+                mac_address: None,
+                ipv4_addresses: Vec::new(),
+                ipv6_addresses: Vec::new(),
+                mtu: Some(65536),
+                operstate: Some("unknown".to_owned()),
+                link_speed_mbps: None,
+            }
+        );
+        assert_eq!(
+            read_interface_info_from(&fixtures.join("missing")),
+            InterfaceInfo::default()
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_addresses() {
+        let addresses = read_addresses();
+
+        assert!(addresses["lo"].ipv4.contains(&"127.0.0.1".to_owned()));
+    }
+
+    #[test]
+    fn test_is_reset_since() {
+        let previous = NetworkCounters {
+            rx_bytes: 100,
+            tx_packets: 10,
+            ..Default::default()
+        };
+
+        assert!(!previous.is_reset_since(&previous));
+        assert!(!NetworkCounters {
+            rx_bytes: 200,
+            ..previous
+        }
+        .is_reset_since(&previous));
+        assert!(NetworkCounters {
+            tx_packets: 2,
+            ..previous
+        }
+        .is_reset_since(&previous));
+        assert!(NetworkCounters::default().is_reset_since(&previous));
+    }
+
+    #[test]
+    fn test_parse_invalid_proc_net_dev() {
+        assert!(parse_proc_net_dev("").is_empty());
+        assert!(parse_proc_net_dev("header\nheader\n  eth0: 1 2 3\n").is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+// NOTE: matches shell like wildcard patterns, '*' matches any sequence of
+//       characters, including an empty one, and '?' matches any single
+//       character, every other character matches only itself
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // NOTE: position of the last '*' in the pattern, and of the text character
+    //       it was matched up to, used for backtracking on a mismatch
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(c) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("coretemp Core 0", "coretemp Core 0"));
+        assert!(!matches_pattern("coretemp Core 0", "coretemp Core 1"));
+        assert!(matches_pattern("coretemp *", "coretemp Core 1"));
+        assert!(matches_pattern("*Core*", "coretemp Core 1"));
+        assert!(matches_pattern("coretemp Core ?", "coretemp Core 1"));
+        assert!(!matches_pattern("coretemp Core ?", "coretemp Core 10"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("a*b*c", "aXbYbZc"));
+        assert!(!matches_pattern("a*b*c", "aXbYbZ"));
+        assert!(!matches_pattern("", "a"));
+    }
+}
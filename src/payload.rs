@@ -0,0 +1,381 @@
+use crate::configuration::PublishMode;
+use crate::resource::ResourceGroup;
+use serde_json::{Map, Value};
+
+// NOTE: resource sections that are published under a topic name which differs
+//       from their key in the serialized resource, or that are split into one
+//       message per array element
+struct Section {
+    key: &'static str,
+    topic: &'static str,
+    split_by: Option<&'static str>,
+}
+
+const SECTIONS: &[Section] = &[
+    Section {
+        key: "cpu",
+        topic: "cpu/summary",
+        split_by: None,
+    },
+    Section {
+        key: "cpus",
+        topic: "cpu",
+        split_by: None,
+    },
+    Section {
+        key: "memory",
+        topic: "memory",
+        split_by: None,
+    },
+    Section {
+        key: "networks",
+        topic: "network",
+        split_by: Some("interface"),
+    },
+];
+
+// NOTE: attributes that identify an element of an array, used instead of
+//       the element index when building topic names
+const KEY_FIELDS: &[&str] = &["id", "interface", "label", "pid", "name", "unit", "path"];
+
+pub struct Message {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+fn find_section(key: &str) -> Option<&'static Section> {
+    SECTIONS.iter().find(|section| section.key == key)
+}
+
+// NOTE: topic of a resource section relative to 'device/<hostname>/system'
+pub fn section_topic(key: &str) -> &str {
+    find_section(key)
+        .map(|section| section.topic)
+        .unwrap_or(key)
+}
+
+// NOTE: MQTT reserves '+' and '#' as wildcards and '/' as the level separator,
+//       names like the ones of network interfaces would otherwise make the
+//       broker reject the topic, or add levels to it
+pub fn topic_level(name: &str) -> String {
+    name.replace(['+', '#', '/'], "_")
+}
+
+pub fn element_key(element: &Value) -> Option<(&'static str, String)> {
+    let object = element.as_object()?;
+    KEY_FIELDS
+        .iter()
+        .find_map(|field| object.get(*field).map(|value| (*field, value)))
+        .map(|(field, value)| match value {
+            Value::String(string) => (field, topic_level(string)),
+            other => (field, topic_level(&other.to_string())),
+        })
+}
+
+fn scalar_payload(value: &Value) -> Vec<u8> {
+    match value {
+        Value::String(string) => string.as_bytes().to_vec(),
+        other => other.to_string().into_bytes(),
+    }
+}
+
+fn flatten(path: &str, value: &Value, values: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(object) => object.iter().for_each(|(key, value)| {
+            flatten(&format!("{}/{}", path, topic_level(key)), value, values)
+        }),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .for_each(|(i, element)| match element_key(element) {
+                Some((field, key)) => {
+                    let mut element = element.clone();
+                    if let Some(object) = element.as_object_mut() {
+                        object.remove(field);
+                    }
+                    flatten(&format!("{}/{}", path, key), &element, values)
+                }
+                None => flatten(&format!("{}/{}", path, i), element, values),
+            }),
+        scalar => values.push((path.to_owned(), scalar.clone())),
+    }
+}
+
+// NOTE: returns every value of the resource together with its path, the path
+//       is the topic of the value in the 'flat' publish mode without the
+//       'device/<hostname>/system/' prefix, i.e. 'cpu/0/load', 'null' values
+//       are returned too, so a value that is unknown for a sample can be told
+//       apart from one that is gone
+pub fn flatten_stats(stats: &Value) -> Vec<(String, Value)> {
+    let mut values = Vec::new();
+    if let Some(stats) = stats.as_object() {
+        for (key, value) in stats {
+            flatten(section_topic(key), value, &mut values);
+        }
+    }
+
+    values
+}
+
+fn build_group_messages(base_topic: &str, stats: &Map<String, Value>) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for (key, value) in stats {
+        let section = find_section(key);
+        let topic = format!("{}/{}", base_topic, section_topic(key));
+
+        match (section.and_then(|section| section.split_by), value) {
+            (Some(split_by), Value::Array(array)) => array.iter().for_each(|element| {
+                let name = element
+                    .get(split_by)
+                    .and_then(|name| name.as_str())
+                    .unwrap_or_default();
+                messages.push(Message {
+                    topic: format!("{}/{}", topic, topic_level(name)),
+                    payload: element.to_string().into_bytes(),
+                })
+            }),
+            _ => messages.push(Message {
+                topic,
+                payload: value.to_string().into_bytes(),
+            }),
+        }
+    }
+
+    messages
+}
+
+fn build_flat_messages(base_topic: &str, stats: &Value) -> Vec<Message> {
+    flatten_stats(stats)
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(path, value)| Message {
+            topic: format!("{}/{}", base_topic, path),
+            payload: scalar_payload(&value),
+        })
+        .collect()
+}
+
+pub fn select_groups(stats: Value, groups: &[ResourceGroup]) -> Value {
+    match stats {
+        Value::Object(mut stats) => {
+            stats.retain(|key, _| {
+                groups
+                    .iter()
+                    .any(|group| group.sections().contains(&key.as_str()))
+            });
+            Value::Object(stats)
+        }
+        other => other,
+    }
+}
+
+pub fn build_messages(base_topic: &str, mode: PublishMode, stats: &Value) -> Vec<Message> {
+    match (mode, stats.as_object()) {
+        (PublishMode::Groups, Some(stats)) => build_group_messages(base_topic, stats),
+        (PublishMode::Flat, Some(_)) => build_flat_messages(base_topic, stats),
+        _ => vec![Message {
+            topic: format!("{}/stats", base_topic),
+            payload: stats.to_string().into_bytes(),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stats() -> Value {
+        json!({
+            "cpu": {
+                "usage": 40.25,
+                "brand": "AMD Ryzen 7 5800X"
+            },
+            "cpus": [
+                { "id": 0, "load": 38.5 },
+                { "id": 1, "load": 42.0 }
+            ],
+            "memory": {
+                "used_bytes": 1,
+                "total_bytes": 15
+            },
+            "networks": [
+                { "interface": "eth0", "rx_bytes": 100, "tx_bytes": 200 },
+                { "interface": "eth1", "rx_bytes": 10, "tx_bytes": 20 }
+            ]
+        })
+    }
+
+    fn as_pairs(messages: Vec<Message>) -> Vec<(String, String)> {
+        messages
+            .into_iter()
+            .map(|message| {
+                (
+                    message.topic,
+                    String::from_utf8(message.payload).expect("payload is not UTF-8"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_groups() {
+        let selected = select_groups(stats(), &[ResourceGroup::Cpu, ResourceGroup::Network]);
+
+        assert_eq!(
+            selected,
+            json!({
+                "cpu": stats()["cpu"],
+                "cpus": stats()["cpus"],
+                "networks": stats()["networks"]
+            })
+        );
+        assert_eq!(select_groups(stats(), &[]), json!({}));
+    }
+
+    #[test]
+    fn test_build_stats_messages() {
+        let messages = as_pairs(build_messages(
+            "device/tum-test/system",
+            PublishMode::Stats,
+            &stats(),
+        ));
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, "device/tum-test/system/stats");
+        assert_eq!(
+            serde_json::from_str::<Value>(&messages[0].1).expect("invalid JSON"),
+            stats()
+        );
+    }
+
+    #[test]
+    fn test_build_group_messages() {
+        let messages = as_pairs(build_messages(
+            "device/tum-test/system",
+            PublishMode::Groups,
+            &stats(),
+        ));
+        let topics: Vec<&str> = messages.iter().map(|(topic, _)| topic.as_str()).collect();
+
+        assert_eq!(
+            topics,
+            vec![
+                "device/tum-test/system/cpu/summary",
+                "device/tum-test/system/cpu",
+                "device/tum-test/system/memory",
+                "device/tum-test/system/network/eth0",
+                "device/tum-test/system/network/eth1",
+            ]
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&messages[2].1).expect("invalid JSON"),
+            stats()["memory"]
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&messages[4].1).expect("invalid JSON"),
+            stats()["networks"][1]
+        );
+    }
+
+    #[test]
+    fn test_build_flat_messages() {
+        let messages = as_pairs(build_messages(
+            "device/tum-test/system",
+            PublishMode::Flat,
+            &stats(),
+        ));
+
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "device/tum-test/system/cpu/summary/brand".to_owned(),
+                    "AMD Ryzen 7 5800X".to_owned()
+                ),
+                (
+                    "device/tum-test/system/cpu/summary/usage".to_owned(),
+                    "40.25".to_owned()
+                ),
+                (
+                    "device/tum-test/system/cpu/0/load".to_owned(),
+                    "38.5".to_owned()
+                ),
+                (
+                    "device/tum-test/system/cpu/1/load".to_owned(),
+                    "42.0".to_owned()
+                ),
+                (
+                    "device/tum-test/system/memory/total_bytes".to_owned(),
+                    "15".to_owned()
+                ),
+                (
+                    "device/tum-test/system/memory/used_bytes".to_owned(),
+                    "1".to_owned()
+                ),
+                (
+                    "device/tum-test/system/network/eth0/rx_bytes".to_owned(),
+                    "100".to_owned()
+                ),
+                (
+                    "device/tum-test/system/network/eth0/tx_bytes".to_owned(),
+                    "200".to_owned()
+                ),
+                (
+                    "device/tum-test/system/network/eth1/rx_bytes".to_owned(),
+                    "10".to_owned()
+                ),
+                (
+                    "device/tum-test/system/network/eth1/tx_bytes".to_owned(),
+                    "20".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_topic_levels() {
+        let stats = json!({
+            "networks": [{ "interface": "wg#0/a+b", "rx_bytes": 1 }]
+        });
+        let topic = "device/tum-test/system";
+
+        assert_eq!(
+            as_pairs(build_messages(topic, PublishMode::Flat, &stats)),
+            vec![(
+                "device/tum-test/system/network/wg_0_a_b/rx_bytes".to_owned(),
+                "1".to_owned()
+            )]
+        );
+        assert_eq!(
+            as_pairs(build_messages(topic, PublishMode::Groups, &stats))[0].0,
+            "device/tum-test/system/network/wg_0_a_b"
+        );
+    }
+
+    #[test]
+    fn test_flatten_labeled_elements() {
+        let stats = json!({
+            "sensors": {
+                "temperatures": [
+                    { "label": "coretemp Core 0", "temperature_celsius": 45.0 },
+                    { "label": "nvme Composite", "temperature_celsius": 38.0 }
+                ]
+            }
+        });
+
+        assert_eq!(
+            flatten_stats(&stats),
+            vec![
+                (
+                    "sensors/temperatures/coretemp Core 0/temperature_celsius".to_owned(),
+                    json!(45.0)
+                ),
+                (
+                    "sensors/temperatures/nvme Composite/temperature_celsius".to_owned(),
+                    json!(38.0)
+                )
+            ]
+        );
+    }
+}
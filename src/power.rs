@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// NOTE: 'percent' is the remaining capacity, 'health_percent' the full
+//       capacity relative to the design capacity, and the time estimates are
+//       reported only while the battery is discharging or charging, with the
+//       ones of the driver preferred over the ones computed from the power draw
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct BatteryResource {
+    pub name: String,
+    pub kind: String,
+    pub status: String,
+    pub percent: Option<f64>,
+    pub time_to_empty_s: Option<u64>,
+    pub time_to_full_s: Option<u64>,
+    pub power_w: Option<f64>,
+    pub health: Option<String>,
+    pub health_percent: Option<f64>,
+    pub cycle_count: Option<u64>,
+}
+
+// NOTE: 'ac_online' is 'null' on systems without a mains power supply, like
+//       desktops that report no power supply at all
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct PowerResource {
+    pub ac_online: Option<bool>,
+    pub batteries: Vec<BatteryResource>,
+}
+
+// NOTE: batteries report either energy in µWh and power in µW, or charge in
+//       µAh and current in µA, both are in the same proportion, so either
+//       pair gives the same percentages and durations
+struct Reserve {
+    now: f64,
+    full: Option<f64>,
+    full_design: Option<f64>,
+    rate: Option<f64>,
+}
+
+fn read_value(directory: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(directory.join(file))
+        .ok()
+        .map(|content| content.trim().to_owned())
+        .filter(|content| !content.is_empty())
+}
+
+fn read_number(directory: &Path, file: &str) -> Option<f64> {
+    read_value(directory, file).and_then(|value| value.parse().ok())
+}
+
+fn read_reserve(directory: &Path) -> Option<Reserve> {
+    ["energy", "charge"].iter().find_map(|quantity| {
+        Some(Reserve {
+            now: read_number(directory, &format!("{}_now", quantity))?,
+            full: read_number(directory, &format!("{}_full", quantity)),
+            full_design: read_number(directory, &format!("{}_full_design", quantity)),
+            // NOTE: some drivers report a negative current while discharging
+            rate: read_number(
+                directory,
+                match *quantity {
+                    "energy" => "power_now",
+                    _ => "current_now",
+                },
+            )
+            .map(f64::abs)
+            .filter(|rate| *rate > 0.0),
+        })
+    })
+}
+
+fn read_battery(name: &str, kind: &str, directory: &Path) -> BatteryResource {
+    let status = read_value(directory, "status")
+        .unwrap_or("Unknown".to_owned())
+        .to_lowercase();
+    let reserve = read_reserve(directory);
+    let seconds = |amount: f64, rate: Option<f64>| rate.map(|rate| (amount / rate * 3600.0) as u64);
+
+    let (time_to_empty_s, time_to_full_s) = match status.as_str() {
+        "discharging" => (
+            read_number(directory, "time_to_empty_now")
+                .map(|seconds| seconds as u64)
+                .or(reserve
+                    .as_ref()
+                    .and_then(|reserve| seconds(reserve.now, reserve.rate))),
+            None,
+        ),
+        "charging" => (
+            None,
+            read_number(directory, "time_to_full_now")
+                .map(|seconds| seconds as u64)
+                .or(reserve.as_ref().and_then(|reserve| {
+                    seconds((reserve.full? - reserve.now).max(0.0), reserve.rate)
+                })),
+        ),
+        _ => (None, None),
+    };
+
+    BatteryResource {
+        name: name.to_owned(),
+        kind: kind.to_lowercase(),
+        percent: read_number(directory, "capacity").or(reserve.as_ref().and_then(|reserve| {
+            reserve
+                .full
+                .filter(|full| *full > 0.0)
+                .map(|full| reserve.now / full * 100.0)
+        })),
+        status,
+        time_to_empty_s,
+        time_to_full_s,
+        power_w: read_number(directory, "power_now").map(|power| power.abs() / 1_000_000.0),
+        health: read_value(directory, "health").map(|health| health.to_lowercase()),
+        health_percent: reserve.as_ref().and_then(|reserve| {
+            match (reserve.full, reserve.full_design) {
+                (Some(full), Some(design)) if design > 0.0 => Some(full / design * 100.0),
+                _ => None,
+            }
+        }),
+        cycle_count: read_number(directory, "cycle_count")
+            .filter(|cycles| *cycles > 0.0)
+            .map(|cycles| cycles as u64),
+    }
+}
+
+// NOTE: batteries of peripherals, like wireless mice, have the 'Device' scope
+//       and are not reported, 'None' when there is no power supply at all
+fn read_power_from(directory: &Path) -> Option<PowerResource> {
+    let mut supplies: Vec<String> = std::fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    supplies.sort();
+
+    let mut ac_online = None;
+    let mut batteries = Vec::new();
+    for name in supplies {
+        let supply = directory.join(&name);
+        if read_value(&supply, "scope").is_some_and(|scope| scope == "Device") {
+            continue;
+        }
+
+        match read_value(&supply, "type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let is_online = read_value(&supply, "online").is_some_and(|online| online == "1");
+                ac_online = Some(ac_online.unwrap_or(false) || is_online);
+            }
+            Some(kind @ "Battery") | Some(kind @ "UPS") => {
+                batteries.push(read_battery(&name, kind, &supply))
+            }
+            _ => (),
+        }
+    }
+
+    match (ac_online, batteries.is_empty()) {
+        (None, true) => None,
+        _ => Some(PowerResource {
+            ac_online,
+            batteries,
+        }),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_power() -> Option<PowerResource> {
+    read_power_from(Path::new("/sys/class/power_supply"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_power() -> Option<PowerResource> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/sys/class/power_supply"
+    );
+
+    #[test]
+    fn test_read_power() {
+        let power = read_power_from(Path::new(FIXTURES)).expect("power supplies");
+
+        assert_eq!(power.ac_online, Some(false));
+        assert_eq!(
+            power.batteries,
+            vec![
+                BatteryResource {
+                    name: "BAT0".to_owned(),
+                    kind: "battery".to_owned(),
+                    status: "discharging".to_owned(),
+                    percent: Some(62.0),
+                    time_to_empty_s: Some(9000),
+                    time_to_full_s: None,
+                    power_w: Some(12.0),
+                    health: None,
+                    health_percent: Some(87.5),
+                    cycle_count: Some(312),
+                },
+                BatteryResource {
+                    name: "BAT1".to_owned(),
+                    kind: "battery".to_owned(),
+                    status: "charging".to_owned(),
+                    percent: Some(40.0),
+                    time_to_empty_s: None,
+                    time_to_full_s: Some(5400),
+                    power_w: None,
+                    health: Some("good".to_owned()),
+                    health_percent: Some(100.0),
+                    cycle_count: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_missing_power() {
+        assert_eq!(read_power_from(&Path::new(FIXTURES).join("missing")), None);
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// NOTE: share of wall time in which tasks were stalled on a resource, averaged
+//       over 10, 60 and 300 seconds, and the total stall time in microseconds
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct PressureStall {
+    avg10: f64,
+    avg60: f64,
+    avg300: f64,
+    total_us: u64,
+}
+
+// NOTE: 'some' is the time in which at least one task was stalled, 'full' the
+//       time in which all non-idle tasks were stalled at once, kernels before
+//       5.13 don't report 'full' for the CPU
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct PressureStalls {
+    some: PressureStall,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full: Option<PressureStall>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct PressureResource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu: Option<PressureStalls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory: Option<PressureStalls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    io: Option<PressureStalls>,
+}
+
+fn parse_stall(fields: &str) -> Option<PressureStall> {
+    let mut avg10 = None;
+    let mut avg60 = None;
+    let mut avg300 = None;
+    let mut total_us = None;
+    for field in fields.split_whitespace() {
+        match field.split_once('=')? {
+            ("avg10", value) => avg10 = value.parse().ok(),
+            ("avg60", value) => avg60 = value.parse().ok(),
+            ("avg300", value) => avg300 = value.parse().ok(),
+            ("total", value) => total_us = value.parse().ok(),
+            _ => (),
+        }
+    }
+
+    Some(PressureStall {
+        avg10: avg10?,
+        avg60: avg60?,
+        avg300: avg300?,
+        total_us: total_us?,
+    })
+}
+
+// NOTE: parses a '/proc/pressure/<resource>' file, 'None' when the mandatory
+//       'some' line is missing or malformed
+pub fn parse_pressure(content: &str) -> Option<PressureStalls> {
+    let mut some = None;
+    let mut full = None;
+    for line in content.lines() {
+        match line.split_once(' ') {
+            Some(("some", fields)) => some = parse_stall(fields),
+            Some(("full", fields)) => full = parse_stall(fields),
+            _ => (),
+        }
+    }
+
+    Some(PressureStalls { some: some?, full })
+}
+
+fn read_pressure_file(directory: &Path, resource: &str) -> Option<PressureStalls> {
+    std::fs::read_to_string(directory.join(resource))
+        .ok()
+        .and_then(|content| parse_pressure(&content))
+}
+
+// NOTE: 'None' when the kernel doesn't support PSI, i.e. it was built without
+//       'CONFIG_PSI' or booted with 'psi=0'
+fn read_pressure_from(directory: &Path) -> Option<PressureResource> {
+    let pressure = PressureResource {
+        cpu: read_pressure_file(directory, "cpu"),
+        memory: read_pressure_file(directory, "memory"),
+        io: read_pressure_file(directory, "io"),
+    };
+
+    match pressure {
+        PressureResource {
+            cpu: None,
+            memory: None,
+            io: None,
+        } => None,
+        pressure => Some(pressure),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_pressure() -> Option<PressureResource> {
+    read_pressure_from(Path::new("/proc/pressure"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_pressure() -> Option<PressureResource> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/proc/pressure");
+
+    #[test]
+    fn test_parse_pressure() {
+        assert_eq!(
+            parse_pressure(include_str!("../tests/fixtures/proc/pressure/io")),
+            Some(PressureStalls {
+                some: PressureStall {
+                    avg10: 4.2,
+                    avg60: 2.75,
+                    avg300: 1.1,
+                    total_us: 95316620,
+                },
+                full: Some(PressureStall {
+                    avg10: 3.8,
+                    avg60: 2.41,
+                    avg300: 0.98,
+                    total_us: 83001457,
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_pressure_without_full() {
+        assert_eq!(
+            parse_pressure("some avg10=0.50 avg60=0.25 avg300=0.00 total=1200\n"),
+            Some(PressureStalls {
+                some: PressureStall {
+                    avg10: 0.5,
+                    avg60: 0.25,
+                    avg300: 0.0,
+                    total_us: 1200,
+                },
+                full: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_pressure() {
+        assert_eq!(parse_pressure(""), None);
+        assert_eq!(
+            parse_pressure("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+        assert_eq!(parse_pressure("some avg10=0.00 avg60=0.00 total=0\n"), None);
+        assert_eq!(
+            parse_pressure("some avg10=x avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_pressure() {
+        let pressure = read_pressure_from(Path::new(FIXTURES)).expect("pressure fixtures");
+
+        assert_eq!(pressure.cpu.map(|cpu| cpu.some.total_us), Some(48216732));
+        assert_eq!(
+            pressure
+                .memory
+                .and_then(|memory| memory.full)
+                .map(|full| full.avg10),
+            Some(0.12)
+        );
+        assert!(pressure.io.is_some());
+    }
+
+    #[test]
+    fn test_read_unsupported_pressure() {
+        assert_eq!(
+            read_pressure_from(&Path::new(FIXTURES).join("missing")),
+            None
+        );
+    }
+}
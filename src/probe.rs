@@ -0,0 +1,567 @@
+use crate::configuration::ProbeConfiguration;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeKind {
+    Tcp,
+    Http,
+    Icmp,
+}
+
+// NOTE: 'latency_ms' is the mean of the successful attempts, 'null' when all
+//       of them failed, 'status_code' is reported only for HTTP probes that
+//       received a response, and 'error' holds the reason of the last failure,
+//       'completed_at' is the Unix time of the end of the probe, as the probes
+//       are reported from the run that completed before the sample
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct ProbeResource {
+    pub name: String,
+    pub kind: ProbeKind,
+    pub target: String,
+    pub completed_at: u64,
+    pub success: bool,
+    pub latency_ms: Option<f64>,
+    pub loss_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Attempt {
+    latency: Option<Duration>,
+    status_code: Option<u16>,
+    error: Option<String>,
+}
+
+impl Attempt {
+    fn failed(error: String) -> Attempt {
+        Attempt {
+            latency: None,
+            status_code: None,
+            error: Some(error),
+        }
+    }
+}
+
+fn resolve(target: &str) -> Result<Vec<SocketAddr>, String> {
+    match target.to_socket_addrs() {
+        Ok(addresses) => Ok(addresses.collect()),
+        Err(err) => Err(format!("can't resolve '{}': {}", target, err)),
+    }
+}
+
+// NOTE: tries the resolved addresses in order, as a host name can resolve to
+//       an IPv6 address that is not reachable, and an IPv4 one that is
+fn connect(target: &str, timeout: Duration) -> Result<TcpStream, String> {
+    let mut error = format!("'{}' resolved to no addresses", target);
+    for address in resolve(target)? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => error = format!("can't connect to '{}': {}", address, err),
+        }
+    }
+
+    Err(error)
+}
+
+fn probe_tcp(target: &str, timeout: Duration) -> Attempt {
+    let start = Instant::now();
+    match connect(target, timeout) {
+        Ok(_) => Attempt {
+            latency: Some(start.elapsed()),
+            status_code: None,
+            error: None,
+        },
+        Err(err) => Attempt::failed(err),
+    }
+}
+
+// NOTE: splits an 'http://host[:port][/path]' URL into the address to connect
+//       to, the value of the 'Host' header, and the requested path
+pub fn parse_http_url(url: &str) -> Option<(String, String, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) if rest[i..].starts_with('/') => (&rest[..i], rest[i..].to_owned()),
+        Some(i) => (&rest[..i], format!("/{}", &rest[i..])),
+        None => (rest, "/".to_owned()),
+    };
+    if authority.is_empty() {
+        return None;
+    }
+
+    let has_port = match authority.rfind(']') {
+        Some(i) => authority[i..].contains(':'),
+        None => authority.contains(':'),
+    };
+    let address = match has_port {
+        true => authority.to_owned(),
+        false => format!("{}:80", authority),
+    };
+
+    Some((address, authority.to_owned(), path))
+}
+
+// NOTE: the status line is enough to measure the latency, the rest of the
+//       response is not read
+fn parse_status_code(response: &[u8]) -> Option<u16> {
+    let line_end = response.windows(2).position(|window| window == b"\r\n")?;
+    let status_line = std::str::from_utf8(&response[..line_end]).ok()?;
+    match status_line.split_whitespace().collect::<Vec<_>>()[..] {
+        [version, code, ..] if version.starts_with("HTTP/") => code.parse().ok(),
+        _ => None,
+    }
+}
+
+fn read_status_code(stream: &mut TcpStream) -> Result<u16, String> {
+    let mut response = Vec::new();
+    let mut buffer = [0; 512];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Err("connection closed before the status line".to_owned()),
+            Ok(read) => {
+                response.extend_from_slice(&buffer[..read]);
+                if let Some(code) = parse_status_code(&response) {
+                    return Ok(code);
+                }
+                if response.windows(2).any(|window| window == b"\r\n") {
+                    return Err("invalid status line".to_owned());
+                }
+            }
+            Err(err) => return Err(format!("can't read the response: {}", err)),
+        }
+    }
+}
+
+// NOTE: the timeout applies to the connection, and to every read and write,
+//       only 'http://' URLs are supported, a status below 400 is a success
+fn probe_http(url: &str, timeout: Duration) -> Attempt {
+    let Some((address, host, path)) = parse_http_url(url) else {
+        return Attempt::failed(format!("invalid URL '{}'", url));
+    };
+
+    let start = Instant::now();
+    let mut stream = match connect(&address, timeout) {
+        Ok(stream) => stream,
+        Err(err) => return Attempt::failed(err),
+    };
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: tum\r\nConnection: close\r\n\r\n",
+        path, host
+    );
+    let status_code = stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .and_then(|_| stream.write_all(request.as_bytes()))
+        .map_err(|err| format!("can't send the request: {}", err))
+        .and_then(|_| read_status_code(&mut stream));
+
+    match status_code {
+        Ok(code) if code < 400 => Attempt {
+            latency: Some(start.elapsed()),
+            status_code: Some(code),
+            error: None,
+        },
+        Ok(code) => Attempt {
+            latency: None,
+            status_code: Some(code),
+            error: Some(format!("unexpected status {}", code)),
+        },
+        Err(err) => Attempt::failed(err),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PingSummary {
+    pub transmitted: u32,
+    pub received: u32,
+    pub average_ms: Option<f64>,
+}
+
+// NOTE: parses the summary of the 'ping' command of iputils and BusyBox, i.e.
+//       '3 packets transmitted, 2 received, 33% packet loss, time 2003ms' and
+//       'rtt min/avg/max/mdev = 0.031/0.042/0.051/0.008 ms'
+pub fn parse_ping_output(output: &str) -> Option<PingSummary> {
+    let mut summary = None;
+    let mut average_ms = None;
+    for line in output.lines() {
+        if line.contains("transmitted") {
+            let counts: Vec<u32> = line
+                .split(',')
+                .filter_map(|field| field.split_whitespace().next()?.parse().ok())
+                .collect();
+            if let [transmitted, received, ..] = counts[..] {
+                summary = Some((transmitted, received));
+            }
+        } else if let Some((_, values)) = line.split_once(" = ") {
+            average_ms = values.split('/').nth(1).and_then(|avg| avg.parse().ok());
+        }
+    }
+
+    summary.map(|(transmitted, received)| PingSummary {
+        transmitted,
+        received,
+        average_ms,
+    })
+}
+
+// NOTE: raw ICMP sockets need privileges, so the probe runs the system 'ping'
+//       command, which is installed with the required capabilities, it takes
+//       the timeout in whole seconds, and the target is validated not to start
+//       with '-', so it can't be taken for an option
+fn probe_icmp(target: &str, count: u32, timeout: Duration) -> Vec<Attempt> {
+    let output = Command::new("ping")
+        .arg("-n")
+        .arg("-q")
+        .args(["-c", &count.to_string()])
+        .args(["-W", &timeout.as_secs().to_string()])
+        .arg(target)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output();
+
+    let (summary, error) = match output {
+        Ok(output) => (
+            parse_ping_output(&String::from_utf8_lossy(&output.stdout)),
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ),
+        Err(err) => (None, format!("can't run 'ping': {}", err)),
+    };
+    let Some(summary) = summary else {
+        let error = match error.is_empty() {
+            true => format!("can't ping '{}'", target),
+            false => error,
+        };
+        return (0..count).map(|_| Attempt::failed(error.clone())).collect();
+    };
+
+    let latency = summary
+        .average_ms
+        .map(|average_ms| Duration::from_secs_f64(average_ms / 1000.0));
+    (0..summary.transmitted.max(count))
+        .map(|i| match i < summary.received {
+            true => Attempt {
+                latency,
+                status_code: None,
+                error: None,
+            },
+            false => Attempt::failed(format!("no reply from '{}'", target)),
+        })
+        .collect()
+}
+
+fn summarize(probe: &ProbeConfiguration, attempts: &[Attempt], completed_at: u64) -> ProbeResource {
+    let latencies: Vec<f64> = attempts
+        .iter()
+        .filter_map(|attempt| attempt.latency)
+        .map(|latency| latency.as_secs_f64() * 1000.0)
+        .collect();
+    let failed = attempts.len() - latencies.len();
+
+    ProbeResource {
+        name: probe.name.to_owned(),
+        kind: probe.kind,
+        target: probe.target.to_owned(),
+        completed_at,
+        success: !latencies.is_empty(),
+        latency_ms: match latencies.is_empty() {
+            true => None,
+            false => Some(latencies.iter().sum::<f64>() / latencies.len() as f64),
+        },
+        loss_percent: match attempts.is_empty() {
+            true => 100.0,
+            false => failed as f64 * 100.0 / attempts.len() as f64,
+        },
+        status_code: attempts
+            .iter()
+            .rev()
+            .find_map(|attempt| attempt.status_code),
+        error: attempts
+            .iter()
+            .rev()
+            .find_map(|attempt| attempt.error.to_owned()),
+    }
+}
+
+// NOTE: the probes run one after another, so a run takes at most the sum of
+//       'count * timeout' of all probes, plus the time to resolve the targets
+pub fn run_probes(probes: &[ProbeConfiguration]) -> Vec<ProbeResource> {
+    probes
+        .iter()
+        .map(|probe| {
+            let attempts: Vec<Attempt> = match probe.kind {
+                ProbeKind::Tcp => (0..probe.count)
+                    .map(|_| probe_tcp(&probe.target, probe.timeout))
+                    .collect(),
+                ProbeKind::Http => (0..probe.count)
+                    .map(|_| probe_http(&probe.target, probe.timeout))
+                    .collect(),
+                ProbeKind::Icmp => probe_icmp(&probe.target, probe.count, probe.timeout),
+            };
+            let completed_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default();
+            summarize(probe, &attempts, completed_at)
+        })
+        .collect()
+}
+
+// NOTE: runs the probes on a thread of its own, so slow probes don't delay the
+//       sampling of the other groups, every 'request' starts a run unless one
+//       is already running, and 'results' returns the last completed run, the
+//       thread stops once the runner is dropped
+#[derive(Debug)]
+pub struct ProbeRunner {
+    requests: SyncSender<()>,
+    results: Arc<Mutex<Vec<ProbeResource>>>,
+}
+
+impl ProbeRunner {
+    pub fn new(probes: Vec<ProbeConfiguration>) -> ProbeRunner {
+        let (requests, pending): (SyncSender<()>, Receiver<()>) = mpsc::sync_channel(1);
+        let results = Arc::new(Mutex::new(Vec::new()));
+
+        let worker_results = Arc::clone(&results);
+        thread::spawn(move || {
+            while pending.recv().is_ok() {
+                let probes = run_probes(&probes);
+                match worker_results.lock() {
+                    Ok(mut results) => *results = probes,
+                    Err(_) => return,
+                }
+            }
+        });
+
+        ProbeRunner { requests, results }
+    }
+
+    pub fn request(&self) {
+        let _ = self.requests.try_send(());
+    }
+
+    pub fn results(&self) -> Vec<ProbeResource> {
+        match self.results.lock() {
+            Ok(results) => results.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn probe(kind: ProbeKind, target: &str, count: u32) -> ProbeConfiguration {
+        ProbeConfiguration {
+            name: "probe".to_owned(),
+            kind,
+            target: target.to_owned(),
+            count,
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    // NOTE: answers every request with the given status line, and stops after
+    //       'count' connections
+    fn serve_http(status: &'static str, count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("local listener");
+        let address = listener.local_addr().expect("listener address");
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.expect("connection");
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = write!(stream, "{}\r\nContent-Length: 0\r\n\r\n", status);
+            }
+        });
+
+        format!("http://{}/health", address)
+    }
+
+    fn closed_port() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("local listener");
+        listener.local_addr().expect("listener address").to_string()
+    }
+
+    #[test]
+    fn test_probe_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("local listener");
+        let target = listener.local_addr().expect("listener address").to_string();
+
+        let probes = run_probes(&[probe(ProbeKind::Tcp, &target, 2)]);
+        assert!(probes[0].success);
+        assert!(probes[0].latency_ms.is_some());
+        assert_eq!(probes[0].loss_percent, 0.0);
+        assert_eq!(probes[0].error, None);
+
+        let probes = run_probes(&[probe(ProbeKind::Tcp, &closed_port(), 1)]);
+        assert!(!probes[0].success);
+        assert_eq!(probes[0].latency_ms, None);
+        assert_eq!(probes[0].loss_percent, 100.0);
+        assert!(probes[0].error.is_some());
+    }
+
+    #[test]
+    fn test_probe_http() {
+        let probes = run_probes(&[
+            probe(
+                ProbeKind::Http,
+                &serve_http("HTTP/1.1 204 No Content", 3),
+                3,
+            ),
+            probe(
+                ProbeKind::Http,
+                &serve_http("HTTP/1.1 503 Service Unavailable", 1),
+                1,
+            ),
+            probe(ProbeKind::Http, &format!("http://{}/", closed_port()), 1),
+        ]);
+
+        assert!(probes[0].success);
+        assert!(probes[0].latency_ms.is_some());
+        assert_eq!(probes[0].loss_percent, 0.0);
+        assert_eq!(probes[0].status_code, Some(204));
+        assert_eq!(
+            (
+                probes[1].success,
+                probes[1].status_code,
+                probes[1].loss_percent
+            ),
+            (false, Some(503), 100.0)
+        );
+        assert_eq!(
+            (
+                probes[2].success,
+                probes[2].status_code,
+                probes[2].loss_percent
+            ),
+            (false, None, 100.0)
+        );
+    }
+
+    #[test]
+    fn test_probe_runner() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("local listener");
+        let target = listener.local_addr().expect("listener address").to_string();
+        let runner = ProbeRunner::new(vec![probe(ProbeKind::Tcp, &target, 1)]);
+
+        assert!(runner.results().is_empty());
+        runner.request();
+        runner.request();
+
+        let start = Instant::now();
+        while runner.results().is_empty() && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let probes = runner.results();
+        assert_eq!(probes.len(), 1);
+        assert!(probes[0].success);
+    }
+
+    #[test]
+    fn test_parse_http_url() {
+        let url = |address: &str, host: &str, path: &str| {
+            Some((address.to_owned(), host.to_owned(), path.to_owned()))
+        };
+
+        assert_eq!(
+            parse_http_url("http://example.com"),
+            url("example.com:80", "example.com", "/")
+        );
+        assert_eq!(
+            parse_http_url("http://10.0.0.2:8080/health?full=1"),
+            url("10.0.0.2:8080", "10.0.0.2:8080", "/health?full=1")
+        );
+        assert_eq!(
+            parse_http_url("http://[::1]/status"),
+            url("[::1]:80", "[::1]", "/status")
+        );
+        assert_eq!(
+            parse_http_url("http://[::1]:9000?probe"),
+            url("[::1]:9000", "[::1]:9000", "/?probe")
+        );
+        assert_eq!(parse_http_url("https://example.com"), None);
+        assert_eq!(parse_http_url("http:///path"), None);
+    }
+
+    #[test]
+    fn test_parse_status_code() {
+        assert_eq!(parse_status_code(b"HTTP/1.1 200 OK\r\n"), Some(200));
+        assert_eq!(
+            parse_status_code(b"HTTP/1.0 302 Found\r\nLocation: /\r\n"),
+            Some(302)
+        );
+        assert_eq!(parse_status_code(b"HTTP/1.1 200"), None);
+        assert_eq!(parse_status_code(b"SSH-2.0-OpenSSH_9.6\r\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ping_output() {
+        assert_eq!(
+            parse_ping_output(include_str!("../tests/fixtures/ping/reachable")),
+            Some(PingSummary {
+                transmitted: 3,
+                received: 2,
+                average_ms: Some(0.412),
+            })
+        );
+        assert_eq!(
+            parse_ping_output(include_str!("../tests/fixtures/ping/unreachable")),
+            Some(PingSummary {
+                transmitted: 2,
+                received: 0,
+                average_ms: None,
+            })
+        );
+        assert_eq!(parse_ping_output(""), None);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let probe = probe(ProbeKind::Icmp, "192.168.1.1", 4);
+        let attempt = |latency_ms: Option<u64>| match latency_ms {
+            Some(latency_ms) => Attempt {
+                latency: Some(Duration::from_millis(latency_ms)),
+                status_code: None,
+                error: None,
+            },
+            None => Attempt::failed("no reply".to_owned()),
+        };
+
+        assert_eq!(
+            summarize(
+                &probe,
+                &[
+                    attempt(Some(10)),
+                    attempt(None),
+                    attempt(Some(30)),
+                    attempt(None)
+                ],
+                1700000000
+            ),
+            ProbeResource {
+                name: "probe".to_owned(),
+                kind: ProbeKind::Icmp,
+                target: "192.168.1.1".to_owned(),
+                completed_at: 1700000000,
+                success: true,
+                latency_ms: Some(20.0),
+                loss_percent: 50.0,
+                status_code: None,
+                error: Some("no reply".to_owned()),
+            }
+        );
+    }
+}
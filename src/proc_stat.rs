@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+// NOTE: cumulative time spent by a CPU in each state, in USER_HZ units,
+//       as reported by '/proc/stat' on Linux
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct CpuTimePercentages {
+    user: f64,
+    nice: f64,
+    system: f64,
+    idle: f64,
+    iowait: f64,
+    irq: f64,
+    softirq: f64,
+    steal: f64,
+}
+
+impl CpuTimes {
+    fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    // NOTE: share of the time between the two readings spent in each state,
+    //       'None' when no time passed, i.e. both readings are the same
+    pub fn percentages_since(&self, previous: &CpuTimes) -> Option<CpuTimePercentages> {
+        let total = self.total().saturating_sub(previous.total());
+        if total == 0 {
+            return None;
+        }
+
+        let percentage = |current: u64, previous: u64| {
+            current.saturating_sub(previous) as f64 * 100.0 / total as f64
+        };
+
+        Some(CpuTimePercentages {
+            user: percentage(self.user, previous.user),
+            nice: percentage(self.nice, previous.nice),
+            system: percentage(self.system, previous.system),
+            idle: percentage(self.idle, previous.idle),
+            iowait: percentage(self.iowait, previous.iowait),
+            irq: percentage(self.irq, previous.irq),
+            softirq: percentage(self.softirq, previous.softirq),
+            steal: percentage(self.steal, previous.steal),
+        })
+    }
+}
+
+// NOTE: returns the CPU times of every 'cpu' line, the aggregate of all CPUs
+//       has the 'None' identifier, and 'cpuN' lines have the 'Some(N)' one
+pub fn parse_proc_stat(content: &str) -> Vec<(Option<usize>, CpuTimes)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = match fields.next()?.strip_prefix("cpu")? {
+                "" => None,
+                id => Some(id.parse().ok()?),
+            };
+            let values: Vec<u64> = fields.map_while(|field| field.parse().ok()).collect();
+            let value = |i: usize| values.get(i).copied().unwrap_or(0);
+
+            Some((
+                id,
+                CpuTimes {
+                    user: value(0),
+                    nice: value(1),
+                    system: value(2),
+                    idle: value(3),
+                    iowait: value(4),
+                    irq: value(5),
+                    softirq: value(6),
+                    steal: value(7),
+                },
+            ))
+        })
+        .collect()
+}
+
+// NOTE: number of a CPU from its 'cpuN' name, the one used by '/proc/stat' and
+//       by sysinfo on Linux, CPUs that are offline are left out of both lists,
+//       so the position of a CPU in them can differ from its number
+pub fn cpu_number(name: &str) -> Option<usize> {
+    name.strip_prefix("cpu")?.parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_cpu_times() -> Vec<(Option<usize>, CpuTimes)> {
+    match std::fs::read_to_string("/proc/stat") {
+        Ok(content) => parse_proc_stat(&content),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_cpu_times() -> Vec<(Option<usize>, CpuTimes)> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = include_str!("../tests/fixtures/proc/stat.first");
+    const SECOND: &str = include_str!("../tests/fixtures/proc/stat.second");
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let cpu_times = parse_proc_stat(FIRST);
+
+        assert_eq!(cpu_times.len(), 3);
+        assert_eq!(
+            cpu_times[0],
+            (
+                None,
+                CpuTimes {
+                    user: 4705,
+                    nice: 150,
+                    system: 1120,
+                    idle: 16250,
+                    iowait: 520,
+                    irq: 12,
+                    softirq: 85,
+                    steal: 40,
+                }
+            )
+        );
+        assert_eq!(cpu_times[1].0, Some(0));
+        assert_eq!(cpu_times[2].0, Some(1));
+        assert_eq!(cpu_times[2].1.softirq, 45);
+    }
+
+    #[test]
+    fn test_cpu_number() {
+        assert_eq!(cpu_number("cpu0"), Some(0));
+        assert_eq!(cpu_number("cpu13"), Some(13));
+        assert_eq!(cpu_number("cpu"), None);
+        assert_eq!(cpu_number("0"), None);
+    }
+
+    #[test]
+    fn test_parse_short_cpu_line() {
+        assert_eq!(
+            parse_proc_stat("cpu 1 2 3 4\ncpux 1 2 3 4\n"),
+            vec![(
+                None,
+                CpuTimes {
+                    user: 1,
+                    nice: 2,
+                    system: 3,
+                    idle: 4,
+                    ..Default::default()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_percentages_since() {
+        let first = parse_proc_stat(FIRST);
+        let second = parse_proc_stat(SECOND);
+
+        assert_eq!(
+            second[0].1.percentages_since(&first[0].1),
+            Some(CpuTimePercentages {
+                user: 30.0,
+                nice: 0.0,
+                system: 10.0,
+                idle: 50.0,
+                iowait: 5.0,
+                irq: 1.0,
+                softirq: 2.0,
+                steal: 2.0,
+            })
+        );
+        assert_eq!(
+            second[2].1.percentages_since(&first[2].1),
+            Some(CpuTimePercentages {
+                user: 20.0,
+                nice: 0.0,
+                system: 10.0,
+                idle: 60.0,
+                iowait: 4.0,
+                irq: 1.0,
+                softirq: 2.0,
+                steal: 3.0,
+            })
+        );
+        assert_eq!(first[1].1.percentages_since(&first[1].1), None);
+    }
+}
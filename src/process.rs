@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessSort {
+    Cpu,
+    Memory,
+    Disk,
+}
+
+impl ProcessSort {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ProcessSort::Cpu => "cpu",
+            ProcessSort::Memory => "memory",
+            ProcessSort::Disk => "disk",
+        }
+    }
+
+    // NOTE: orders the processes from the most to the least demanding one
+    fn compare(&self, a: &ProcessResource, b: &ProcessResource) -> Ordering {
+        match *self {
+            ProcessSort::Cpu => b.cpu_usage.total_cmp(&a.cpu_usage),
+            ProcessSort::Memory => b.rss_bytes.cmp(&a.rss_bytes),
+            ProcessSort::Disk => (b.disk_read_bytes + b.disk_written_bytes)
+                .cmp(&(a.disk_read_bytes + a.disk_written_bytes)),
+        }
+    }
+}
+
+// NOTE: 'cpu_usage' is relative to a single CPU, so it can exceed 100% for
+//       multithreaded processes, disk usage is counted since the previous
+//       sample
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct ProcessResource {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub user: Option<String>,
+    pub cpu_usage: f32,
+    pub rss_bytes: u64,
+    pub virtual_memory_bytes: u64,
+    pub disk_read_bytes: u64,
+    pub disk_written_bytes: u64,
+}
+
+// NOTE: joins the arguments of a process, and cuts the result to at most
+//       'length' characters
+pub fn truncate_cmdline(cmd: &[String], length: usize) -> String {
+    cmd.join(" ").chars().take(length).collect()
+}
+
+// NOTE: processes with equal usage are ordered by their pid, so the selection
+//       doesn't change between samples of an idle system
+pub fn top_processes(
+    processes: &[ProcessResource],
+    sort: ProcessSort,
+    count: usize,
+) -> Vec<ProcessResource> {
+    let mut sorted: Vec<&ProcessResource> = processes.iter().collect();
+    sorted.sort_by(|a, b| sort.compare(a, b).then(a.pid.cmp(&b.pid)));
+
+    sorted.into_iter().take(count).cloned().collect()
+}
+
+// NOTE: tracks the oldest instance of a watched process between samples, a
+//       restart is detected when it is gone while the process is running, so
+//       workers that come and go next to it, like the ones of a web server or
+//       a database, are not counted as restarts
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct WatchState {
+    oldest_pid: Option<u32>,
+    was_running: bool,
+    restarts: u64,
+}
+
+impl WatchState {
+    // NOTE: 'instances' holds the start time and the pid of every running
+    //       instance, so the first one is the oldest
+    pub fn update(&mut self, instances: BTreeSet<(u64, u32)>) {
+        let oldest_pid = instances.iter().next().map(|(_, pid)| *pid);
+        if self.was_running && oldest_pid.is_some() && oldest_pid != self.oldest_pid {
+            self.restarts += 1;
+        }
+
+        self.was_running |= oldest_pid.is_some();
+        self.oldest_pid = oldest_pid;
+    }
+
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, cpu_usage: f32, rss_bytes: u64, disk_bytes: u64) -> ProcessResource {
+        ProcessResource {
+            pid,
+            name: format!("process{}", pid),
+            cmdline: String::new(),
+            user: None,
+            cpu_usage,
+            rss_bytes,
+            virtual_memory_bytes: rss_bytes * 2,
+            disk_read_bytes: disk_bytes,
+            disk_written_bytes: disk_bytes,
+        }
+    }
+
+    fn pids(processes: &[ProcessResource]) -> Vec<u32> {
+        processes.iter().map(|process| process.pid).collect()
+    }
+
+    #[test]
+    fn test_top_processes() {
+        let processes = vec![
+            process(1, 0.5, 4000, 0),
+            process(20, 95.0, 1000, 10),
+            process(300, 12.0, 9000, 500),
+            process(4000, 0.5, 2000, 20),
+        ];
+
+        assert_eq!(
+            pids(&top_processes(&processes, ProcessSort::Cpu, 3)),
+            vec![20, 300, 1]
+        );
+        assert_eq!(
+            pids(&top_processes(&processes, ProcessSort::Memory, 2)),
+            vec![300, 1]
+        );
+        assert_eq!(
+            pids(&top_processes(&processes, ProcessSort::Disk, 10)),
+            vec![300, 4000, 20, 1]
+        );
+        assert!(top_processes(&processes, ProcessSort::Cpu, 0).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_cmdline() {
+        let cmd = vec![
+            "/usr/bin/python3".to_owned(),
+            "-m".to_owned(),
+            "http.server".to_owned(),
+        ];
+
+        assert_eq!(
+            truncate_cmdline(&cmd, 256),
+            "/usr/bin/python3 -m http.server"
+        );
+        assert_eq!(truncate_cmdline(&cmd, 16), "/usr/bin/python3");
+        assert_eq!(truncate_cmdline(&["žćč".to_owned()], 2), "žć");
+        assert_eq!(truncate_cmdline(&[], 16), "");
+    }
+
+    #[test]
+    fn test_watch_restarts() {
+        let mut state = WatchState::default();
+        let mut update = |instances: &[(u64, u32)]| {
+            state.update(instances.iter().copied().collect());
+            state.restarts()
+        };
+
+        assert_eq!(update(&[]), 0);
+        assert_eq!(update(&[(10, 100)]), 0);
+        assert_eq!(update(&[(10, 100)]), 0);
+        assert_eq!(update(&[(10, 100), (11, 101), (11, 102)]), 0);
+        assert_eq!(update(&[(10, 100), (11, 102)]), 0);
+        assert_eq!(update(&[(20, 200)]), 1);
+        assert_eq!(update(&[(20, 200), (21, 201)]), 1);
+        assert_eq!(update(&[(21, 201)]), 2);
+        assert_eq!(update(&[]), 2);
+        assert_eq!(update(&[]), 2);
+        assert_eq!(update(&[(30, 300)]), 3);
+    }
+
+    #[test]
+    fn test_watch_worker_turnover() {
+        let mut state = WatchState::default();
+        let mut update = |instances: &[(u64, u32)]| {
+            state.update(instances.iter().copied().collect());
+            state.restarts()
+        };
+
+        assert_eq!(update(&[(10, 100), (11, 101), (11, 102)]), 0);
+        assert_eq!(update(&[(10, 100), (11, 102), (15, 103)]), 0);
+        assert_eq!(update(&[(10, 100), (16, 104), (16, 105)]), 0);
+        assert_eq!(update(&[(10, 100)]), 0);
+        assert_eq!(update(&[(10, 100), (17, 106)]), 0);
+        assert_eq!(update(&[(17, 106)]), 1);
+    }
+}
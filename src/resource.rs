@@ -1,35 +1,308 @@
+use crate::cgroup::{CgroupCollector, CgroupResource, CGROUP_ROOT};
+use crate::configuration::{
+    Configuration, ProcessesConfiguration, SensorsConfiguration, WatchConfiguration,
+};
+use crate::docker::{ContainerResource, DockerRunner};
+use crate::gpu::{default_gpu_backends, GpuBackend, GpuResource};
+use crate::hwmon::read_hwmon;
+use crate::meminfo::read_meminfo;
+use crate::network::{InterfaceInfo, NetworkCounters, NetworkSource, SystemNetworkSource};
+use crate::power::{read_power, PowerResource};
+use crate::pressure::{read_pressure, PressureResource};
+use crate::probe::{ProbeResource, ProbeRunner};
+use crate::proc_stat::{cpu_number, read_cpu_times, CpuTimePercentages, CpuTimes};
+use crate::process::{top_processes, truncate_cmdline, ProcessResource, WatchState};
+use crate::sockets::{read_sockets, SocketsResource};
+use crate::systemd::{read_systemd_units, SystemdUnitResource};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use sysinfo::{CpuExt, NetworkExt, System, SystemExt};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::time::Instant;
+use sysinfo::{
+    ComponentExt, CpuExt, CpuRefreshKind, PidExt, ProcessExt, System, SystemExt, UserExt,
+};
+
+#[derive(Debug, PartialEq, Eq, Hash, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceGroup {
+    Cgroups,
+    Containers,
+    Cpu,
+    Gpu,
+    Memory,
+    Network,
+    Power,
+    Pressure,
+    Probes,
+    Processes,
+    Sensors,
+    Sockets,
+    System,
+    Systemd,
+    Watch,
+}
+
+impl ResourceGroup {
+    pub const ALL: [ResourceGroup; 15] = [
+        ResourceGroup::Cgroups,
+        ResourceGroup::Containers,
+        ResourceGroup::Cpu,
+        ResourceGroup::Gpu,
+        ResourceGroup::Memory,
+        ResourceGroup::Network,
+        ResourceGroup::Power,
+        ResourceGroup::Pressure,
+        ResourceGroup::Probes,
+        ResourceGroup::Processes,
+        ResourceGroup::Sensors,
+        ResourceGroup::Sockets,
+        ResourceGroup::System,
+        ResourceGroup::Systemd,
+        ResourceGroup::Watch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ResourceGroup::Cgroups => "cgroups",
+            ResourceGroup::Containers => "containers",
+            ResourceGroup::Cpu => "cpu",
+            ResourceGroup::Gpu => "gpu",
+            ResourceGroup::Memory => "memory",
+            ResourceGroup::Network => "network",
+            ResourceGroup::Power => "power",
+            ResourceGroup::Pressure => "pressure",
+            ResourceGroup::Probes => "probes",
+            ResourceGroup::Processes => "processes",
+            ResourceGroup::Sensors => "sensors",
+            ResourceGroup::Sockets => "sockets",
+            ResourceGroup::System => "system",
+            ResourceGroup::Systemd => "systemd",
+            ResourceGroup::Watch => "watch",
+        }
+    }
+
+    // NOTE: keys of the serialized resource that hold the data of the group
+    pub fn sections(&self) -> &'static [&'static str] {
+        match *self {
+            ResourceGroup::Cgroups => &["cgroups"],
+            ResourceGroup::Containers => &["containers"],
+            ResourceGroup::Cpu => &["cpu", "cpus"],
+            ResourceGroup::Gpu => &["gpus"],
+            ResourceGroup::Memory => &["memory"],
+            ResourceGroup::Network => &["networks"],
+            ResourceGroup::Power => &["power"],
+            ResourceGroup::Pressure => &["pressure"],
+            ResourceGroup::Probes => &["probes"],
+            ResourceGroup::Processes => &["processes"],
+            ResourceGroup::Sensors => &["sensors"],
+            ResourceGroup::Sockets => &["sockets"],
+            ResourceGroup::System => &["system"],
+            ResourceGroup::Systemd => &["systemd"],
+            ResourceGroup::Watch => &["watch"],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct CPUSummaryResource {
+    usage: f32,
+    brand: String,
+    vendor: String,
+    physical_cores: Option<usize>,
+    logical_cores: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    times: Option<CpuTimePercentages>,
+}
 
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 struct CPUResource {
-    id: u8,
+    id: usize,
     load: f32,
+    frequency_mhz: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    times: Option<CpuTimePercentages>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 struct MemoryResource {
     used_bytes: u64,
     total_bytes: u64,
+    available_bytes: u64,
+    free_bytes: u64,
+    swap_used_bytes: u64,
+    swap_total_bytes: u64,
+    // NOTE: read from '/proc/meminfo', so only reported on Linux
+    #[serde(skip_serializing_if = "Option::is_none")]
+    buffers_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cached_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slab_bytes: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct TemperatureSensorResource {
+    label: String,
+    temperature_celsius: f32,
+    max_celsius: f32,
+    critical_celsius: Option<f32>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct FanSensorResource {
+    label: String,
+    speed_rpm: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct VoltageSensorResource {
+    label: String,
+    voltage_v: f64,
+}
+
+// NOTE: fans and voltages are read from '/sys/class/hwmon', so they are only
+//       reported on Linux when enabled in the configuration
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+struct SensorsResource {
+    temperatures: Vec<TemperatureSensorResource>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fans: Vec<FanSensorResource>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    voltages: Vec<VoltageSensorResource>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct WatchedProcessResource {
+    name: String,
+    running: bool,
+    instances: usize,
+    cpu_usage: f32,
+    rss_bytes: u64,
+    restarts: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+struct SystemResource {
+    load_average_1m: f64,
+    load_average_5m: f64,
+    load_average_15m: f64,
+    uptime_s: u64,
+    boot_time_s: u64,
+    os_name: Option<String>,
+    os_version: Option<String>,
+    kernel_version: Option<String>,
+    architecture: String,
 }
 
+// NOTE: the rates are computed from the counters of the previous sample, and
+//       are 'null' on the first sample of an interface, and on the sample
+//       after its counters were reset or wrapped
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct NetworkResource {
     interface: String,
+    #[serde(flatten)]
+    info: InterfaceInfo,
     rx_bytes: u64,
     tx_bytes: u64,
-    rx_error_bytes: u64,
-    tx_error_bytes: u64,
-    rx_speed_bps: f64,
-    tx_speed_bps: f64,
+    rx_packets: u64,
+    tx_packets: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_dropped: u64,
+    tx_dropped: u64,
+    rx_bytes_per_s: Option<f64>,
+    tx_bytes_per_s: Option<f64>,
+    rx_bits_per_s: Option<f64>,
+    tx_bits_per_s: Option<f64>,
+    rx_packets_per_s: Option<f64>,
+    tx_packets_per_s: Option<f64>,
+    rx_errors_per_s: Option<f64>,
+    tx_errors_per_s: Option<f64>,
+    rx_dropped_per_s: Option<f64>,
+    tx_dropped_per_s: Option<f64>,
     #[serde(skip)]
-    tx_bytes_old: u64,
+    counters_old: NetworkCounters,
+    // NOTE: monotonic time of the previous sample, so changes of the system
+    //       clock don't distort the rates
     #[serde(skip)]
-    rx_bytes_old: u64,
-    #[serde(skip)]
-    last_synced_timestamp: u128,
+    last_synced_timestamp: Option<Instant>,
+}
+
+impl NetworkResource {
+    fn new(
+        interface: &str,
+        info: InterfaceInfo,
+        counters: NetworkCounters,
+        now: Instant,
+    ) -> NetworkResource {
+        let mut network = NetworkResource {
+            interface: interface.to_owned(),
+            info,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            rx_packets: 0,
+            tx_packets: 0,
+            rx_errors: 0,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            rx_bytes_per_s: None,
+            tx_bytes_per_s: None,
+            rx_bits_per_s: None,
+            tx_bits_per_s: None,
+            rx_packets_per_s: None,
+            tx_packets_per_s: None,
+            rx_errors_per_s: None,
+            tx_errors_per_s: None,
+            rx_dropped_per_s: None,
+            tx_dropped_per_s: None,
+            counters_old: counters,
+            last_synced_timestamp: Some(now),
+        };
+        network.set_counters(counters);
+
+        network
+    }
+
+    fn set_counters(&mut self, counters: NetworkCounters) {
+        self.rx_bytes = counters.rx_bytes;
+        self.tx_bytes = counters.tx_bytes;
+        self.rx_packets = counters.rx_packets;
+        self.tx_packets = counters.tx_packets;
+        self.rx_errors = counters.rx_errors;
+        self.tx_errors = counters.tx_errors;
+        self.rx_dropped = counters.rx_dropped;
+        self.tx_dropped = counters.tx_dropped;
+    }
+
+    fn update(&mut self, counters: NetworkCounters, now: Instant) {
+        let old = self.counters_old;
+        let elapsed_s = self
+            .last_synced_timestamp
+            .map(|timestamp| now.saturating_duration_since(timestamp).as_secs_f64())
+            .filter(|elapsed_s| *elapsed_s > 0.0 && !counters.is_reset_since(&old));
+        let per_second = |current: u64, previous: u64| {
+            elapsed_s.map(|elapsed_s| current.saturating_sub(previous) as f64 / elapsed_s)
+        };
+
+        self.set_counters(counters);
+        self.rx_bytes_per_s = per_second(counters.rx_bytes, old.rx_bytes);
+        self.tx_bytes_per_s = per_second(counters.tx_bytes, old.tx_bytes);
+        self.rx_bits_per_s = self.rx_bytes_per_s.map(|bytes| bytes * 8.0);
+        self.tx_bits_per_s = self.tx_bytes_per_s.map(|bytes| bytes * 8.0);
+        self.rx_packets_per_s = per_second(counters.rx_packets, old.rx_packets);
+        self.tx_packets_per_s = per_second(counters.tx_packets, old.tx_packets);
+        self.rx_errors_per_s = per_second(counters.rx_errors, old.rx_errors);
+        self.tx_errors_per_s = per_second(counters.tx_errors, old.tx_errors);
+        self.rx_dropped_per_s = per_second(counters.rx_dropped, old.rx_dropped);
+        self.tx_dropped_per_s = per_second(counters.tx_dropped, old.tx_dropped);
+
+        self.counters_old = counters;
+        self.last_synced_timestamp = Some(now);
+    }
 }
 
 impl PartialEq for NetworkResource {
@@ -61,169 +334,604 @@ impl Ord for NetworkResource {
 pub struct Resource {
     #[serde(skip)]
     system_info: System,
+    // NOTE: CPU times of the previous refresh, keyed the same way as the
+    //       output of 'read_cpu_times'
+    #[serde(skip)]
+    cpu_times_old: HashMap<Option<usize>, CpuTimes>,
+    #[serde(skip)]
+    sensors_configuration: SensorsConfiguration,
+    #[serde(skip)]
+    processes_configuration: Option<ProcessesConfiguration>,
+    #[serde(skip)]
+    watch_configuration: Vec<WatchConfiguration>,
+    // NOTE: pids and restarts of the watched processes, in the same order as
+    //       'watch_configuration'
+    #[serde(skip)]
+    watch_states: Vec<WatchState>,
+    #[serde(skip)]
+    systemd_units: Vec<String>,
+    #[serde(skip)]
+    cgroup_collector: CgroupCollector,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cgroups: Vec<CgroupResource>,
+    #[serde(skip)]
+    docker_runner: Option<DockerRunner>,
+    // NOTE: 'None' when the engine is not configured, or can't be reached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    containers: Option<Vec<ContainerResource>>,
+    #[serde(skip)]
+    probe_runner: Option<ProbeRunner>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    probes: Vec<ProbeResource>,
+    cpu: CPUSummaryResource,
     cpus: Vec<CPUResource>,
+    #[serde(skip, default = "default_gpu_backends")]
+    gpu_backends: Vec<Box<dyn GpuBackend>>,
+    // NOTE: empty on systems without a GPU supported by one of the backends
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    gpus: Vec<GpuResource>,
     memory: MemoryResource,
     networks: Vec<NetworkResource>,
+    #[serde(skip, default = "default_network_source")]
+    network_source: Box<dyn NetworkSource>,
+    // NOTE: 'None' on systems without a power supply, and on platforms other
+    //       than Linux
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power: Option<PowerResource>,
+    // NOTE: 'None' on kernels without Pressure Stall Information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pressure: Option<PressureResource>,
+    // NOTE: top processes keyed by the sort criterion, 'None' when the
+    //       'processes' configuration option is not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    processes: Option<BTreeMap<String, Vec<ProcessResource>>>,
+    sensors: SensorsResource,
+    // NOTE: 'None' on platforms other than Linux
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sockets: Option<SocketsResource>,
+    system: SystemResource,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    systemd: Vec<SystemdUnitResource>,
+    // NOTE: watched processes are reported even when they are not running
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    watch: Vec<WatchedProcessResource>,
+}
+
+fn default_network_source() -> Box<dyn NetworkSource> {
+    Box::new(SystemNetworkSource::default())
 }
 
 impl Resource {
-    pub fn new() -> Resource {
+    pub fn new(configuration: &Configuration) -> Resource {
         let mut resource = Resource {
             system_info: System::new(),
+            cpu_times_old: HashMap::new(),
+            sensors_configuration: configuration.sensors.clone(),
+            processes_configuration: configuration.processes.clone(),
+            watch_configuration: configuration.watch.clone(),
+            watch_states: vec![WatchState::default(); configuration.watch.len()],
+            systemd_units: configuration.systemd_units.clone(),
+            cgroup_collector: CgroupCollector::new(Path::new(CGROUP_ROOT), &configuration.cgroups),
+            cgroups: Vec::new(),
+            docker_runner: configuration
+                .docker
+                .as_ref()
+                .map(|docker| DockerRunner::new(&docker.socket)),
+            containers: None,
+            probe_runner: match configuration.probes.is_empty() {
+                true => None,
+                false => Some(ProbeRunner::new(configuration.probes.clone())),
+            },
+            probes: Vec::new(),
+            cpu: CPUSummaryResource {
+                usage: -1.0,
+                brand: String::new(),
+                vendor: String::new(),
+                physical_cores: None,
+                logical_cores: 0,
+                times: None,
+            },
             cpus: Vec::new(),
+            gpu_backends: match configuration.is_group_enabled(ResourceGroup::Gpu) {
+                true => default_gpu_backends(),
+                false => Vec::new(),
+            },
+            gpus: Vec::new(),
             memory: MemoryResource {
                 used_bytes: 0,
                 total_bytes: 0,
+                available_bytes: 0,
+                free_bytes: 0,
+                swap_used_bytes: 0,
+                swap_total_bytes: 0,
+                buffers_bytes: None,
+                cached_bytes: None,
+                shared_bytes: None,
+                slab_bytes: None,
             },
             networks: Vec::new(),
+            network_source: default_network_source(),
+            power: None,
+            pressure: None,
+            processes: None,
+            sensors: SensorsResource::default(),
+            sockets: None,
+            system: SystemResource {
+                load_average_1m: 0.0,
+                load_average_5m: 0.0,
+                load_average_15m: 0.0,
+                uptime_s: 0,
+                boot_time_s: 0,
+                os_name: None,
+                os_version: None,
+                kernel_version: None,
+                architecture: String::new(),
+            },
+            systemd: Vec::new(),
+            watch: Vec::new(),
         };
 
-        resource.system_info.refresh_cpu();
+        resource
+            .system_info
+            .refresh_cpu_specifics(CpuRefreshKind::everything());
+        let global_cpu = resource.system_info.global_cpu_info();
+        resource.cpu.brand = global_cpu.brand().to_owned();
+        resource.cpu.vendor = global_cpu.vendor_id().to_owned();
+        resource.cpu.physical_cores = resource.system_info.physical_core_count();
+        resource.cpu.logical_cores = resource.system_info.cpus().len();
         for i in 0..resource.system_info.cpus().len() {
             resource.cpus.push(CPUResource {
-                id: i as u8,
+                id: i,
                 load: -1.0,
+                frequency_mhz: 0,
+                times: None,
             });
         }
+        resource.cpu_times_old = read_cpu_times().into_iter().collect();
 
-        let time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or(Duration::new(0, 0))
-            .as_micros();
-
-        resource.system_info.refresh_networks_list();
-        resource.system_info.refresh_networks();
-        for (interface, _) in resource.system_info.networks() {
-            resource.networks.push(NetworkResource {
-                interface: interface.to_owned(),
-                rx_bytes: 0,
-                tx_bytes: 0,
-                rx_error_bytes: 0,
-                tx_error_bytes: 0,
-                rx_speed_bps: 0.0,
-                tx_speed_bps: 0.0,
-                tx_bytes_old: 0,
-                rx_bytes_old: 0,
-                last_synced_timestamp: time,
-            });
+        resource.refresh_networks(Instant::now());
+
+        resource.system_info.refresh_components_list();
+        if resource.processes_configuration.is_some() {
+            resource.system_info.refresh_users_list();
         }
-        resource.networks.sort();
+        resource.watch = configuration
+            .watch
+            .iter()
+            .map(|watch| WatchedProcessResource {
+                name: watch.name.to_owned(),
+                running: false,
+                instances: 0,
+                cpu_usage: 0.0,
+                rss_bytes: 0,
+                restarts: 0,
+            })
+            .collect();
+
+        resource.system.boot_time_s = resource.system_info.boot_time();
+        resource.system.os_name = resource.system_info.name();
+        resource.system.os_version = resource.system_info.os_version();
+        resource.system.kernel_version = resource.system_info.kernel_version();
+        resource.system.architecture = std::env::consts::ARCH.to_owned();
 
         resource
     }
 
-    pub fn refresh(&mut self) {
-        self.system_info.refresh_cpu();
+    pub fn refresh(&mut self, groups: &[ResourceGroup]) {
+        // NOTE: the process table is shared by the 'processes' and 'watch'
+        //       groups, and refreshed once, as the CPU usage of a process is
+        //       computed since its previous refresh
+        let is_process_table_used = groups.iter().any(|group| match group {
+            ResourceGroup::Processes => self.processes_configuration.is_some(),
+            ResourceGroup::Watch => !self.watch_configuration.is_empty(),
+            _ => false,
+        });
+        if is_process_table_used {
+            self.system_info.refresh_processes();
+        }
+
+        groups.iter().for_each(|group| match group {
+            ResourceGroup::Cgroups => self.refresh_cgroups(),
+            ResourceGroup::Containers => self.refresh_containers(),
+            ResourceGroup::Cpu => self.refresh_cpus(),
+            ResourceGroup::Gpu => self.refresh_gpus(),
+            ResourceGroup::Memory => self.refresh_memory(),
+            ResourceGroup::Network => self.refresh_networks(Instant::now()),
+            ResourceGroup::Power => self.refresh_power(),
+            ResourceGroup::Pressure => self.refresh_pressure(),
+            ResourceGroup::Probes => self.refresh_probes(),
+            ResourceGroup::Processes => self.refresh_processes(),
+            ResourceGroup::Sensors => self.refresh_sensors(),
+            ResourceGroup::Sockets => self.refresh_sockets(),
+            ResourceGroup::System => self.refresh_system(),
+            ResourceGroup::Systemd => self.refresh_systemd(),
+            ResourceGroup::Watch => self.refresh_watch(),
+        });
+    }
+
+    // NOTE: the CPU times are matched to the CPUs by the number in their name,
+    //       as the position of a CPU differs from its number once a CPU before
+    //       it is offline
+    fn refresh_cpus(&mut self) {
+        let cpu_times = read_cpu_times();
+        let mut percentages: HashMap<Option<usize>, CpuTimePercentages> = cpu_times
+            .iter()
+            .filter_map(|(id, times)| {
+                let times_old = self.cpu_times_old.get(id)?;
+                Some((*id, times.percentages_since(times_old)?))
+            })
+            .collect();
+        self.cpu_times_old = cpu_times.into_iter().collect();
+
+        self.system_info
+            .refresh_cpu_specifics(CpuRefreshKind::new().with_cpu_usage().with_frequency());
+        self.cpu.usage = self.system_info.global_cpu_info().cpu_usage();
+        self.cpu.times = percentages.remove(&None);
         self.system_info
             .cpus()
             .iter()
             .enumerate()
-            .for_each(
-                |(i, x)| match self.cpus.iter_mut().find(|y| y.id == i as u8) {
-                    Some(cpu) => cpu.load = x.cpu_usage(),
-                    None => print!("WRN: can't find cpu with id: '{}'", i),
-                },
-            );
+            .for_each(|(i, x)| match self.cpus.iter_mut().find(|y| y.id == i) {
+                Some(cpu) => {
+                    cpu.load = x.cpu_usage();
+                    cpu.frequency_mhz = x.frequency();
+                    cpu.times =
+                        cpu_number(x.name()).and_then(|number| percentages.remove(&Some(number)));
+                }
+                None => eprintln!("WRN: can't find cpu with id: '{}'", i),
+            });
+    }
 
-        let time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or(Duration::new(0, 0))
-            .as_micros();
+    // NOTE: interfaces that show up after the start are reported with their
+    //       counters and 'null' rates, and interfaces that are gone are removed,
+    //       the attributes of an interface are read again when its operational
+    //       state changes, or its counters were reset by a restart, while the
+    //       addresses, that can change with the link up, are read every time
+    fn refresh_networks(&mut self, now: Instant) {
+        let counters = self.network_source.counters();
+        let mut addresses = self.network_source.addresses();
 
-        self.system_info.refresh_networks();
-        self.system_info
-            .networks()
-            .into_iter()
-            .for_each(|(interface, network_data)| {
-                match self.networks.iter_mut().find(|y| y.interface.eq(interface)) {
-                    Some(network) => {
-                        network.tx_bytes = network_data.transmitted();
-                        network.rx_bytes = network_data.received();
-                        network.rx_error_bytes = network_data.total_errors_on_received();
-                        network.rx_error_bytes = network_data.total_errors_on_transmitted();
-
-                        network.rx_speed_bps = network.rx_bytes.abs_diff(network.rx_bytes_old)
-                            as f64
-                            / time.abs_diff(network.last_synced_timestamp) as f64;
-                        network.tx_speed_bps = network.tx_bytes.abs_diff(network.tx_bytes_old)
-                            as f64
-                            / time.abs_diff(network.last_synced_timestamp) as f64;
-                        network.rx_bytes_old = network.rx_bytes;
-                        network.tx_bytes_old = network.tx_bytes;
-                        network.last_synced_timestamp = time;
+        self.networks
+            .retain(|network| counters.contains_key(&network.interface));
+        for (interface, counters) in counters {
+            match self
+                .networks
+                .iter_mut()
+                .find(|y| y.interface.eq(&interface))
+            {
+                Some(network) => {
+                    let operstate = self.network_source.operstate(&interface);
+                    if operstate != network.info.operstate
+                        || counters.is_reset_since(&network.counters_old)
+                    {
+                        network.info = self.network_source.interface_info(&interface);
                     }
-                    None => print!(
-                        "WRN: can't find network interface with interface name: '{}'",
-                        interface
-                    ),
+                    network
+                        .info
+                        .set_addresses(addresses.remove(&interface).unwrap_or_default());
+                    network.update(counters, now);
                 }
-            });
+                None => {
+                    let mut info = self.network_source.interface_info(&interface);
+                    info.set_addresses(addresses.remove(&interface).unwrap_or_default());
+                    self.networks
+                        .push(NetworkResource::new(&interface, info, counters, now));
+                }
+            }
+        }
+        self.networks.sort();
+    }
+
+    fn refresh_system(&mut self) {
+        let load_average = self.system_info.load_average();
+        self.system.load_average_1m = load_average.one;
+        self.system.load_average_5m = load_average.five;
+        self.system.load_average_15m = load_average.fifteen;
+        self.system.uptime_s = self.system_info.uptime();
+    }
+
+    fn refresh_processes(&mut self) {
+        let configuration = match self.processes_configuration {
+            Some(ref configuration) => configuration,
+            None => return,
+        };
+
+        let processes: Vec<ProcessResource> = self
+            .system_info
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let disk_usage = process.disk_usage();
+                ProcessResource {
+                    pid: pid.as_u32(),
+                    name: process.name().to_owned(),
+                    cmdline: truncate_cmdline(process.cmd(), configuration.cmdline_length),
+                    user: process
+                        .user_id()
+                        .and_then(|uid| self.system_info.get_user_by_id(uid))
+                        .map(|user| user.name().to_owned()),
+                    cpu_usage: process.cpu_usage(),
+                    rss_bytes: process.memory(),
+                    virtual_memory_bytes: process.virtual_memory(),
+                    disk_read_bytes: disk_usage.read_bytes,
+                    disk_written_bytes: disk_usage.written_bytes,
+                }
+            })
+            .collect();
+
+        self.processes = Some(
+            configuration
+                .sort_by
+                .iter()
+                .map(|sort| {
+                    (
+                        sort.name().to_owned(),
+                        top_processes(&processes, *sort, configuration.count),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    fn refresh_cgroups(&mut self) {
+        self.cgroups = self.cgroup_collector.collect(Instant::now());
+    }
+
+    // NOTE: like the probes, the sample holds the containers of the previous
+    //       collection
+    fn refresh_containers(&mut self) {
+        if let Some(ref runner) = self.docker_runner {
+            runner.request();
+            self.containers = runner.results();
+        }
+    }
+
+    // NOTE: the requested run completes after the sample, so the sample holds
+    //       the results of the previous run, marked with their 'completed_at'
+    fn refresh_probes(&mut self) {
+        if let Some(ref runner) = self.probe_runner {
+            runner.request();
+            self.probes = runner.results();
+        }
+    }
+
+    fn refresh_systemd(&mut self) {
+        self.systemd = read_systemd_units(&self.systemd_units);
+    }
+
+    fn refresh_watch(&mut self) {
+        for ((watch, state), resource) in self
+            .watch_configuration
+            .iter()
+            .zip(self.watch_states.iter_mut())
+            .zip(self.watch.iter_mut())
+        {
+            let instances: Vec<_> = self
+                .system_info
+                .processes()
+                .iter()
+                .filter(|(_, process)| watch.matches(process.name(), &process.cmd().join(" ")))
+                .collect();
+
+            state.update(
+                instances
+                    .iter()
+                    .map(|(pid, process)| (process.start_time(), pid.as_u32()))
+                    .collect::<BTreeSet<(u64, u32)>>(),
+            );
+            resource.running = !instances.is_empty();
+            resource.instances = instances.len();
+            resource.cpu_usage = instances
+                .iter()
+                .map(|(_, process)| process.cpu_usage())
+                .sum();
+            resource.rss_bytes = instances.iter().map(|(_, process)| process.memory()).sum();
+            resource.restarts = state.restarts();
+        }
+    }
+
+    fn refresh_sensors(&mut self) {
+        self.system_info.refresh_components();
+        let configuration = &self.sensors_configuration;
 
+        self.sensors.temperatures = self
+            .system_info
+            .components()
+            .iter()
+            .filter(|component| configuration.is_selected(component.label()))
+            .map(|component| TemperatureSensorResource {
+                label: component.label().to_owned(),
+                temperature_celsius: component.temperature(),
+                max_celsius: component.max(),
+                critical_celsius: component.critical(),
+            })
+            .collect();
+
+        if !configuration.hwmon {
+            return;
+        }
+
+        let hwmon = read_hwmon();
+        self.sensors.fans = hwmon
+            .fans
+            .into_iter()
+            .filter(|(label, _)| configuration.is_selected(label))
+            .map(|(label, speed_rpm)| FanSensorResource { label, speed_rpm })
+            .collect();
+        self.sensors.voltages = hwmon
+            .voltages
+            .into_iter()
+            .filter(|(label, _)| configuration.is_selected(label))
+            .map(|(label, voltage_v)| VoltageSensorResource { label, voltage_v })
+            .collect();
+    }
+
+    fn refresh_gpus(&mut self) {
+        self.gpus = self
+            .gpu_backends
+            .iter_mut()
+            .flat_map(|backend| backend.collect())
+            .collect();
+    }
+
+    fn refresh_power(&mut self) {
+        self.power = read_power();
+    }
+
+    fn refresh_pressure(&mut self) {
+        self.pressure = read_pressure();
+    }
+
+    fn refresh_sockets(&mut self) {
+        self.sockets = read_sockets();
+    }
+
+    fn refresh_memory(&mut self) {
         self.system_info.refresh_memory();
         self.memory.used_bytes = self.system_info.used_memory();
         self.memory.total_bytes = self.system_info.total_memory();
+        self.memory.available_bytes = self.system_info.available_memory();
+        self.memory.free_bytes = self.system_info.free_memory();
+        self.memory.swap_used_bytes = self.system_info.used_swap();
+        self.memory.swap_total_bytes = self.system_info.total_swap();
+
+        let meminfo = read_meminfo();
+        self.memory.buffers_bytes = meminfo.buffers;
+        self.memory.cached_bytes = meminfo.cached;
+        self.memory.shared_bytes = meminfo.shared;
+        self.memory.slab_bytes = meminfo.slab;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::InterfaceAddresses;
+    use crate::process::ProcessSort;
     use assert_json_diff::assert_json_eq;
     use serde_json::json;
+    use std::collections::VecDeque;
     use std::process::{Command, Stdio};
+    use std::time::Duration;
 
     fn crate_resource() -> Resource {
         let mut resource = Resource {
             system_info: System::new(),
+            cpu_times_old: HashMap::new(),
+            sensors_configuration: SensorsConfiguration::default(),
+            processes_configuration: None,
+            watch_configuration: Vec::new(),
+            watch_states: Vec::new(),
+            systemd_units: Vec::new(),
+            cgroup_collector: CgroupCollector::default(),
+            cgroups: Vec::new(),
+            docker_runner: None,
+            containers: None,
+            probe_runner: None,
+            probes: Vec::new(),
+            cpu: CPUSummaryResource {
+                usage: -1.0,
+                brand: String::new(),
+                vendor: String::new(),
+                physical_cores: None,
+                logical_cores: 0,
+                times: None,
+            },
             cpus: Vec::new(),
+            gpu_backends: default_gpu_backends(),
+            gpus: Vec::new(),
             memory: MemoryResource {
                 used_bytes: 0,
                 total_bytes: 0,
+                available_bytes: 0,
+                free_bytes: 0,
+                swap_used_bytes: 0,
+                swap_total_bytes: 0,
+                buffers_bytes: None,
+                cached_bytes: None,
+                shared_bytes: None,
+                slab_bytes: None,
             },
             networks: Vec::new(),
+            network_source: default_network_source(),
+            power: None,
+            pressure: None,
+            processes: None,
+            sensors: SensorsResource::default(),
+            sockets: None,
+            system: SystemResource {
+                load_average_1m: 0.0,
+                load_average_5m: 0.0,
+                load_average_15m: 0.0,
+                uptime_s: 0,
+                boot_time_s: 0,
+                os_name: None,
+                os_version: None,
+                kernel_version: None,
+                architecture: String::new(),
+            },
+            systemd: Vec::new(),
+            watch: Vec::new(),
         };
 
-        resource.system_info.refresh_cpu();
+        resource
+            .system_info
+            .refresh_cpu_specifics(CpuRefreshKind::everything());
+        let global_cpu = resource.system_info.global_cpu_info();
+        resource.cpu.brand = global_cpu.brand().to_owned();
+        resource.cpu.vendor = global_cpu.vendor_id().to_owned();
+        resource.cpu.physical_cores = resource.system_info.physical_core_count();
+        resource.cpu.logical_cores = resource.system_info.cpus().len();
         for i in 0..resource.system_info.cpus().len() {
             resource.cpus.push(CPUResource {
-                id: i as u8,
+                id: i,
                 load: -1.0,
+                frequency_mhz: 0,
+                times: None,
             });
         }
+        resource.cpu_times_old = read_cpu_times().into_iter().collect();
 
-        let time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or(Duration::new(0, 0))
-            .as_micros();
-
-        resource.system_info.refresh_networks_list();
-        resource.system_info.refresh_networks();
-        for (interface, _) in resource.system_info.networks() {
-            resource.networks.push(NetworkResource {
-                interface: interface.to_owned(),
-                rx_bytes: 0,
-                tx_bytes: 0,
-                rx_error_bytes: 0,
-                tx_error_bytes: 0,
-                rx_speed_bps: 0.0,
-                tx_speed_bps: 0.0,
-                tx_bytes_old: 0,
-                rx_bytes_old: 0,
-                last_synced_timestamp: time,
-            });
+        let now = Instant::now();
+        let mut addresses = resource.network_source.addresses();
+        for (interface, counters) in resource.network_source.counters() {
+            let mut info = resource.network_source.interface_info(&interface);
+            info.set_addresses(addresses.remove(&interface).unwrap_or_default());
+            resource
+                .networks
+                .push(NetworkResource::new(&interface, info, counters, now));
         }
         resource.networks.sort();
 
+        resource.system.boot_time_s = resource.system_info.boot_time();
+        resource.system.os_name = resource.system_info.name();
+        resource.system.os_version = resource.system_info.os_version();
+        resource.system.kernel_version = resource.system_info.kernel_version();
+        resource.system.architecture = std::env::consts::ARCH.to_owned();
+
         resource
     }
 
     #[test]
     fn test_create_resource() {
         let def_resource = crate_resource();
-        let resource = Resource::new();
+        let resource = Resource::new(&Configuration::default());
 
-        assert_eq!(def_resource.cpus.iter().eq(resource.cpus.iter()), true);
+        assert!(def_resource.cpu == resource.cpu);
+        assert!(def_resource.cpus.iter().eq(resource.cpus.iter()));
+        assert!(def_resource.gpus == resource.gpus);
         assert!(def_resource.memory == resource.memory);
+        assert!(def_resource.power == resource.power);
+        assert!(def_resource.pressure == resource.pressure);
+        assert!(def_resource.sensors == resource.sensors);
+        assert!(def_resource.sockets == resource.sockets);
+        assert!(def_resource.processes == resource.processes);
+        assert!(def_resource.watch == resource.watch);
+        assert!(def_resource.systemd == resource.systemd);
+        assert!(def_resource.cgroups == resource.cgroups);
+        assert!(def_resource.containers == resource.containers);
+        assert!(def_resource.probes == resource.probes);
+        assert!(def_resource.system == resource.system);
         def_resource.networks.iter().for_each(|x| {
             println!(
                 "interface: {}\ndef received: {}\ndef transmitted: {}\n",
@@ -236,16 +944,14 @@ mod tests {
                 x.interface, x.rx_bytes, x.tx_bytes
             )
         });
-        assert_eq!(
-            def_resource.networks.iter().eq(resource.networks.iter()),
-            true
-        );
+        assert!(def_resource.networks.iter().eq(resource.networks.iter()));
     }
 
     #[test]
+    #[allow(clippy::suspicious_command_arg_space)]
     fn test_refresh_resources() {
         let def_resource = crate_resource();
-        let mut resource = Resource::new();
+        let mut resource = Resource::new(&Configuration::default());
         let status = Command::new("ping")
             .arg("-c 2")
             .arg("localhost")
@@ -253,106 +959,611 @@ mod tests {
             .status()
             .expect("failed to run ping");
 
-        resource.refresh();
+        resource.refresh(&ResourceGroup::ALL);
 
-        assert_eq!(status.success(), true);
+        assert!(status.success());
 
         assert!(def_resource.cpus.len() == resource.cpus.len());
-        assert_eq!(def_resource.cpus.iter().eq(resource.cpus.iter()), false);
+        assert!(!def_resource.cpus.iter().eq(resource.cpus.iter()));
 
         assert!(def_resource.memory != resource.memory);
 
         assert!(def_resource.networks.len() == resource.networks.len());
+        assert!(!def_resource.networks.iter().eq(resource.networks.iter()));
+    }
+
+    #[test]
+    fn test_refresh_processes() {
+        let configuration = Configuration {
+            processes: Some(ProcessesConfiguration {
+                count: 2,
+                sort_by: vec![ProcessSort::Memory],
+                cmdline_length: 16,
+            }),
+            ..Default::default()
+        };
+        let mut resource = Resource::new(&configuration);
+
+        resource.refresh(&[ResourceGroup::Processes]);
+
+        let processes = resource.processes.expect("top processes");
+        assert_eq!(processes.keys().collect::<Vec<_>>(), vec!["memory"]);
+        assert!(!processes["memory"].is_empty() && processes["memory"].len() <= 2);
+        assert!(processes["memory"][0].rss_bytes >= processes["memory"].last().unwrap().rss_bytes);
+        assert!(processes["memory"]
+            .iter()
+            .all(|process| process.cmdline.chars().count() <= 16));
+    }
+
+    #[test]
+    fn test_refresh_watch() {
+        let configuration = Configuration {
+            watch: vec![
+                WatchConfiguration {
+                    name: "self".to_owned(),
+                    process: None,
+                    cmdline: Some("*tum*".to_owned()),
+                },
+                WatchConfiguration {
+                    name: "absent".to_owned(),
+                    process: Some("tum-absent-process".to_owned()),
+                    cmdline: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let mut resource = Resource::new(&configuration);
+
+        resource.refresh(&[ResourceGroup::Watch]);
+
+        assert_eq!(resource.watch.len(), 2);
+        assert!(resource.watch[0].running);
+        assert!(resource.watch[0].instances >= 1);
+        assert!(resource.watch[0].rss_bytes > 0);
         assert_eq!(
-            def_resource.networks.iter().eq(resource.networks.iter()),
-            false
+            resource.watch[1],
+            WatchedProcessResource {
+                name: "absent".to_owned(),
+                running: false,
+                instances: 0,
+                cpu_usage: 0.0,
+                rss_bytes: 0,
+                restarts: 0,
+            }
         );
     }
 
+    // NOTE: replays recorded counters and operational states, one of each
+    //       per refresh, the MTU counts the reads of the interface attributes
+    #[derive(Debug)]
+    struct RecordedNetworkSource {
+        counters: VecDeque<HashMap<String, NetworkCounters>>,
+        operstates: VecDeque<&'static str>,
+        operstate: &'static str,
+        ipv4_addresses: VecDeque<&'static str>,
+        ipv4_address: &'static str,
+        info_reads: u32,
+    }
+
+    impl NetworkSource for RecordedNetworkSource {
+        fn counters(&mut self) -> HashMap<String, NetworkCounters> {
+            if let Some(operstate) = self.operstates.pop_front() {
+                self.operstate = operstate;
+            }
+            self.counters.pop_front().unwrap_or_default()
+        }
+
+        fn interface_info(&mut self, _interface: &str) -> InterfaceInfo {
+            self.info_reads += 1;
+            InterfaceInfo {
+                mtu: Some(self.info_reads),
+                operstate: Some(self.operstate.to_owned()),
+                ..Default::default()
+            }
+        }
+
+        fn addresses(&mut self) -> HashMap<String, InterfaceAddresses> {
+            if let Some(address) = self.ipv4_addresses.pop_front() {
+                self.ipv4_address = address;
+            }
+            HashMap::from([(
+                "eth0".to_owned(),
+                InterfaceAddresses {
+                    ipv4: vec![self.ipv4_address.to_owned()],
+                    ipv6: Vec::new(),
+                },
+            )])
+        }
+
+        fn operstate(&mut self, _interface: &str) -> Option<String> {
+            Some(self.operstate.to_owned())
+        }
+    }
+
+    fn network_counters(
+        interface: &str,
+        counters: NetworkCounters,
+    ) -> HashMap<String, NetworkCounters> {
+        HashMap::from([(interface.to_owned(), counters)])
+    }
+
+    fn recorded_network_resource(
+        counters: Vec<HashMap<String, NetworkCounters>>,
+        operstates: Vec<&'static str>,
+        ipv4_addresses: Vec<&'static str>,
+    ) -> (Resource, Instant) {
+        let mut resource = Resource::new(&Configuration::default());
+        let start = Instant::now();
+
+        resource.networks = Vec::new();
+        resource.network_source = Box::new(RecordedNetworkSource {
+            counters: VecDeque::from(counters),
+            operstates: VecDeque::from(operstates),
+            operstate: "up",
+            ipv4_addresses: VecDeque::from(ipv4_addresses),
+            ipv4_address: "10.0.0.2",
+            info_reads: 0,
+        });
+        resource.refresh_networks(start);
+
+        (resource, start)
+    }
+
+    #[test]
+    fn test_refresh_network_errors() {
+        let (mut resource, start) = recorded_network_resource(
+            vec![
+                network_counters("eth0", NetworkCounters::default()),
+                network_counters(
+                    "eth0",
+                    NetworkCounters {
+                        rx_errors: 7,
+                        tx_errors: 2,
+                        rx_dropped: 5,
+                        tx_dropped: 1,
+                        ..Default::default()
+                    },
+                ),
+            ],
+            Vec::new(),
+            Vec::new(),
+        );
+
+        resource.refresh_networks(start + Duration::from_secs(1));
+
+        let network = &resource.networks[0];
+        assert_eq!(network.rx_errors, 7);
+        assert_eq!(network.tx_errors, 2);
+        assert_eq!(network.rx_dropped, 5);
+        assert_eq!(network.tx_dropped, 1);
+    }
+
+    #[test]
+    fn test_refresh_network_rates() {
+        let (mut resource, start) = recorded_network_resource(
+            vec![
+                network_counters(
+                    "eth0",
+                    NetworkCounters {
+                        rx_bytes: 1_000_000,
+                        tx_bytes: 2_000,
+                        rx_packets: 1000,
+                        tx_packets: 500,
+                        rx_errors: 10,
+                        tx_errors: 0,
+                        rx_dropped: 4,
+                        tx_dropped: 2,
+                    },
+                ),
+                network_counters(
+                    "eth0",
+                    NetworkCounters {
+                        rx_bytes: 1_250_000,
+                        tx_bytes: 3_000,
+                        rx_packets: 1600,
+                        tx_packets: 700,
+                        rx_errors: 14,
+                        tx_errors: 2,
+                        rx_dropped: 4,
+                        tx_dropped: 8,
+                    },
+                ),
+            ],
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(resource.networks[0].rx_bytes, 1_000_000);
+        assert_eq!(resource.networks[0].tx_dropped, 2);
+        assert_eq!(resource.networks[0].rx_bytes_per_s, None);
+
+        resource.refresh_networks(start + Duration::from_secs(2));
+
+        let network = &resource.networks[0];
+        assert_eq!(network.rx_bytes, 1_250_000);
+        assert_eq!(network.rx_bytes_per_s, Some(125_000.0));
+        assert_eq!(network.tx_bytes_per_s, Some(500.0));
+        assert_eq!(network.rx_bits_per_s, Some(1_000_000.0));
+        assert_eq!(network.tx_bits_per_s, Some(4_000.0));
+        assert_eq!(network.rx_packets, 1600);
+        assert_eq!(network.tx_packets, 700);
+        assert_eq!(network.rx_packets_per_s, Some(300.0));
+        assert_eq!(network.tx_packets_per_s, Some(100.0));
+        assert_eq!(network.rx_errors_per_s, Some(2.0));
+        assert_eq!(network.tx_errors_per_s, Some(1.0));
+        assert_eq!(network.rx_dropped_per_s, Some(0.0));
+        assert_eq!(network.tx_dropped_per_s, Some(3.0));
+    }
+
+    #[test]
+    fn test_refresh_network_reset() {
+        let counters = |rx_bytes: u64| NetworkCounters {
+            rx_bytes,
+            tx_bytes: rx_bytes,
+            ..Default::default()
+        };
+        let (mut resource, start) = recorded_network_resource(
+            vec![
+                network_counters("ppp0", counters(u64::MAX - 100)),
+                network_counters("ppp0", counters(u64::MAX)),
+                network_counters("ppp0", counters(400)),
+                network_counters("ppp0", counters(600)),
+                network_counters("wlan0", counters(1000)),
+                network_counters("wlan0", counters(1500)),
+                network_counters("wlan0", counters(1500)),
+            ],
+            Vec::new(),
+            Vec::new(),
+        );
+        let mut refresh = |seconds: u64| {
+            resource.refresh_networks(start + Duration::from_secs(seconds));
+            resource
+                .networks
+                .iter()
+                .map(|network| (network.interface.clone(), network.rx_bytes_per_s))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(refresh(1), vec![("ppp0".to_owned(), Some(100.0))]);
+        // NOTE: the counter wrapped, or the interface was restarted
+        assert_eq!(refresh(2), vec![("ppp0".to_owned(), None)]);
+        assert_eq!(refresh(4), vec![("ppp0".to_owned(), Some(100.0))]);
+        assert_eq!(refresh(5), vec![("wlan0".to_owned(), None)]);
+        assert_eq!(refresh(5), vec![("wlan0".to_owned(), None)]);
+        assert_eq!(refresh(6), vec![("wlan0".to_owned(), Some(0.0))]);
+    }
+
+    #[test]
+    fn test_refresh_network_info() {
+        let counters = |rx_bytes: u64| {
+            network_counters(
+                "eth0",
+                NetworkCounters {
+                    rx_bytes,
+                    ..Default::default()
+                },
+            )
+        };
+        let (mut resource, start) = recorded_network_resource(
+            vec![
+                counters(100),
+                counters(200),
+                counters(250),
+                counters(300),
+                counters(50),
+                counters(60),
+            ],
+            vec!["up", "up", "up", "down", "down", "down"],
+            vec!["10.0.0.2", "10.0.0.2", "10.0.0.7"],
+        );
+        let mut refresh = |seconds: u64| {
+            resource.refresh_networks(start + Duration::from_secs(seconds));
+            resource.networks[0].info.clone()
+        };
+
+        assert_eq!(
+            refresh(1),
+            InterfaceInfo {
+                ipv4_addresses: vec!["10.0.0.2".to_owned()],
+                mtu: Some(1),
+                operstate: Some("up".to_owned()),
+                ..Default::default()
+            }
+        );
+        // NOTE: the address changed while the link stayed up
+        let info = refresh(2);
+        assert_eq!(info.ipv4_addresses, vec!["10.0.0.7".to_owned()]);
+        assert_eq!(info.mtu, Some(1));
+        let info = refresh(3);
+        assert_eq!(info.mtu, Some(2));
+        assert_eq!(info.operstate, Some("down".to_owned()));
+        // NOTE: the counters were reset by a restart of the interface
+        assert_eq!(refresh(4).mtu, Some(3));
+        assert_eq!(refresh(5).mtu, Some(3));
+    }
+
     #[test]
     fn test_resource_serialization() {
         let mut resource = Resource {
             system_info: System::new(),
+            cpu_times_old: HashMap::new(),
+            sensors_configuration: SensorsConfiguration::default(),
+            processes_configuration: None,
+            watch_configuration: Vec::new(),
+            watch_states: Vec::new(),
+            systemd_units: Vec::new(),
+            cgroup_collector: CgroupCollector::default(),
+            cgroups: Vec::new(),
+            docker_runner: None,
+            containers: None,
+            probe_runner: None,
+            probes: Vec::new(),
+            cpu: CPUSummaryResource {
+                usage: -1.0,
+                brand: String::new(),
+                vendor: String::new(),
+                physical_cores: None,
+                logical_cores: 0,
+                times: None,
+            },
             cpus: Vec::new(),
+            gpu_backends: default_gpu_backends(),
+            gpus: Vec::new(),
             memory: MemoryResource {
                 used_bytes: 0,
                 total_bytes: 0,
+                available_bytes: 0,
+                free_bytes: 0,
+                swap_used_bytes: 0,
+                swap_total_bytes: 0,
+                buffers_bytes: None,
+                cached_bytes: None,
+                shared_bytes: None,
+                slab_bytes: None,
             },
             networks: Vec::new(),
+            network_source: default_network_source(),
+            power: None,
+            pressure: None,
+            processes: None,
+            sensors: SensorsResource::default(),
+            sockets: None,
+            system: SystemResource {
+                load_average_1m: 0.0,
+                load_average_5m: 0.0,
+                load_average_15m: 0.0,
+                uptime_s: 0,
+                boot_time_s: 0,
+                os_name: None,
+                os_version: None,
+                kernel_version: None,
+                architecture: String::new(),
+            },
+            systemd: Vec::new(),
+            watch: Vec::new(),
+        };
+
+        resource.cpu = CPUSummaryResource {
+            usage: 20.0,
+            brand: "AMD Ryzen 7 5800X".to_owned(),
+            vendor: "AuthenticAMD".to_owned(),
+            physical_cores: Some(2),
+            logical_cores: 3,
+            times: None,
         };
 
-        resource.cpus.push(CPUResource { id: 0, load: 38.0 });
-        resource.cpus.push(CPUResource { id: 1, load: 11.0 });
-        resource.cpus.push(CPUResource { id: 2, load: 12.0 });
+        resource.cpus.push(CPUResource {
+            id: 0,
+            load: 38.0,
+            frequency_mhz: 3800,
+            times: None,
+        });
+        resource.cpus.push(CPUResource {
+            id: 1,
+            load: 11.0,
+            frequency_mhz: 2200,
+            times: None,
+        });
+        resource.cpus.push(CPUResource {
+            id: 2,
+            load: 12.0,
+            frequency_mhz: 2200,
+            times: None,
+        });
 
         resource.memory.total_bytes = 15;
         resource.memory.used_bytes = 1;
+        resource.memory.available_bytes = 12;
+        resource.memory.free_bytes = 4;
+        resource.memory.swap_used_bytes = 2;
+        resource.memory.swap_total_bytes = 8;
+        resource.memory.cached_bytes = Some(6);
 
         resource.networks.push(NetworkResource {
-            interface: "eth0".to_owned(),
             rx_bytes: 100,
             tx_bytes: 200,
-            rx_error_bytes: 0,
-            tx_error_bytes: 0,
-            rx_speed_bps: 300.0,
-            tx_speed_bps: 400.0,
-            tx_bytes_old: 0,
-            rx_bytes_old: 0,
-            last_synced_timestamp: 0,
+            rx_packets: 10,
+            tx_packets: 20,
+            rx_errors: 1,
+            tx_errors: 2,
+            rx_bytes_per_s: Some(300.0),
+            tx_bytes_per_s: Some(400.0),
+            rx_bits_per_s: Some(2400.0),
+            tx_bits_per_s: Some(3200.0),
+            rx_packets_per_s: Some(3.0),
+            tx_packets_per_s: Some(4.0),
+            ..NetworkResource::new(
+                "eth0",
+                InterfaceInfo {
+                    mac_address: Some("52:54:00:12:34:56".to_owned()),
+                    ipv4_addresses: vec!["192.168.1.10".to_owned()],
+                    ipv6_addresses: vec!["fe80::5054:ff:fe12:3456".to_owned()],
+                    mtu: Some(1500),
+                    operstate: Some("up".to_owned()),
+                    link_speed_mbps: Some(1000),
+                },
+                NetworkCounters::default(),
+                Instant::now(),
+            )
         });
 
         resource.networks.push(NetworkResource {
-            interface: "eth1".to_owned(),
             rx_bytes: 10,
             tx_bytes: 20,
-            rx_error_bytes: 0,
-            tx_error_bytes: 0,
-            rx_speed_bps: 30.0,
-            tx_speed_bps: 40.0,
-            tx_bytes_old: 0,
-            rx_bytes_old: 0,
-            last_synced_timestamp: 0,
+            rx_packets: 1,
+            tx_packets: 2,
+            rx_errors: 1,
+            tx_errors: 2,
+            ..NetworkResource::new(
+                "eth1",
+                InterfaceInfo::default(),
+                NetworkCounters::default(),
+                Instant::now(),
+            )
+        });
+
+        resource
+            .sensors
+            .temperatures
+            .push(TemperatureSensorResource {
+                label: "coretemp Package id 0".to_owned(),
+                temperature_celsius: 48.0,
+                max_celsius: 61.0,
+                critical_celsius: Some(100.0),
+            });
+        resource.sensors.fans.push(FanSensorResource {
+            label: "nct6775 CPU Fan".to_owned(),
+            speed_rpm: 1250,
         });
 
+        resource.system = SystemResource {
+            load_average_1m: 0.5,
+            load_average_5m: 0.25,
+            load_average_15m: 0.125,
+            uptime_s: 3600,
+            boot_time_s: 1697700000,
+            os_name: Some("Debian GNU/Linux".to_owned()),
+            os_version: Some("12".to_owned()),
+            kernel_version: Some("6.1.0-13-amd64".to_owned()),
+            architecture: "x86_64".to_owned(),
+        };
+
         let expected_resource = json!({
+                "cpu": {
+                    "usage": resource.cpu.usage,
+                    "brand": resource.cpu.brand,
+                    "vendor": resource.cpu.vendor,
+                    "physical_cores": resource.cpu.physical_cores,
+                    "logical_cores": resource.cpu.logical_cores
+                },
                 "cpus": [
                     {
                         "id": resource.cpus[0].id,
-                        "load": resource.cpus[0].load
+                        "load": resource.cpus[0].load,
+                        "frequency_mhz": resource.cpus[0].frequency_mhz
                     },
                     {
                         "id": resource.cpus[1].id,
-                        "load": resource.cpus[1].load
+                        "load": resource.cpus[1].load,
+                        "frequency_mhz": resource.cpus[1].frequency_mhz
                     },
                     {
                         "id": resource.cpus[2].id,
-                        "load": resource.cpus[2].load
+                        "load": resource.cpus[2].load,
+                        "frequency_mhz": resource.cpus[2].frequency_mhz
                     }
                 ],
                 "memory": {
                     "used_bytes": resource.memory.used_bytes,
-                    "total_bytes": resource.memory.total_bytes
+                    "total_bytes": resource.memory.total_bytes,
+                    "available_bytes": resource.memory.available_bytes,
+                    "free_bytes": resource.memory.free_bytes,
+                    "swap_used_bytes": resource.memory.swap_used_bytes,
+                    "swap_total_bytes": resource.memory.swap_total_bytes,
+                    "cached_bytes": resource.memory.cached_bytes
                 },
                 "networks": [
                     {
                         "interface": resource.networks[0].interface,
+                        "mac_address": resource.networks[0].info.mac_address,
+                        "ipv4_addresses": resource.networks[0].info.ipv4_addresses,
+                        "ipv6_addresses": resource.networks[0].info.ipv6_addresses,
+                        "mtu": resource.networks[0].info.mtu,
+                        "operstate": resource.networks[0].info.operstate,
+                        "link_speed_mbps": resource.networks[0].info.link_speed_mbps,
                         "rx_bytes": resource.networks[0].rx_bytes,
                         "tx_bytes": resource.networks[0].tx_bytes,
-                        "rx_error_bytes": resource.networks[0].rx_error_bytes,
-                        "tx_error_bytes": resource.networks[0].tx_error_bytes,
-                        "rx_speed_bps": resource.networks[0].rx_speed_bps,
-                        "tx_speed_bps":resource.networks[0].tx_speed_bps
+                        "rx_packets": resource.networks[0].rx_packets,
+                        "tx_packets": resource.networks[0].tx_packets,
+                        "rx_errors": resource.networks[0].rx_errors,
+                        "tx_errors": resource.networks[0].tx_errors,
+                        "rx_dropped": resource.networks[0].rx_dropped,
+                        "tx_dropped": resource.networks[0].tx_dropped,
+                        "rx_bytes_per_s": resource.networks[0].rx_bytes_per_s,
+                        "tx_bytes_per_s": resource.networks[0].tx_bytes_per_s,
+                        "rx_bits_per_s": resource.networks[0].rx_bits_per_s,
+                        "tx_bits_per_s": resource.networks[0].tx_bits_per_s,
+                        "rx_packets_per_s": resource.networks[0].rx_packets_per_s,
+                        "tx_packets_per_s": resource.networks[0].tx_packets_per_s,
+                        "rx_errors_per_s": resource.networks[0].rx_errors_per_s,
+                        "tx_errors_per_s": resource.networks[0].tx_errors_per_s,
+                        "rx_dropped_per_s": resource.networks[0].rx_dropped_per_s,
+                        "tx_dropped_per_s": resource.networks[0].tx_dropped_per_s
                     },
                     {
                         "interface": resource.networks[1].interface,
+                        "mac_address": resource.networks[1].info.mac_address,
+                        "ipv4_addresses": resource.networks[1].info.ipv4_addresses,
+                        "ipv6_addresses": resource.networks[1].info.ipv6_addresses,
+                        "mtu": resource.networks[1].info.mtu,
+                        "operstate": resource.networks[1].info.operstate,
+                        "link_speed_mbps": resource.networks[1].info.link_speed_mbps,
                         "rx_bytes": resource.networks[1].rx_bytes,
                         "tx_bytes": resource.networks[1].tx_bytes,
-                        "rx_error_bytes": resource.networks[1].rx_error_bytes,
-                        "tx_error_bytes": resource.networks[1].tx_error_bytes,
-                        "rx_speed_bps": resource.networks[1].rx_speed_bps,
-                        "tx_speed_bps":resource.networks[1].tx_speed_bps
+                        "rx_packets": resource.networks[1].rx_packets,
+                        "tx_packets": resource.networks[1].tx_packets,
+                        "rx_errors": resource.networks[1].rx_errors,
+                        "tx_errors": resource.networks[1].tx_errors,
+                        "rx_dropped": resource.networks[1].rx_dropped,
+                        "tx_dropped": resource.networks[1].tx_dropped,
+                        "rx_bytes_per_s": resource.networks[1].rx_bytes_per_s,
+                        "tx_bytes_per_s": resource.networks[1].tx_bytes_per_s,
+                        "rx_bits_per_s": resource.networks[1].rx_bits_per_s,
+                        "tx_bits_per_s": resource.networks[1].tx_bits_per_s,
+                        "rx_packets_per_s": resource.networks[1].rx_packets_per_s,
+                        "tx_packets_per_s": resource.networks[1].tx_packets_per_s,
+                        "rx_errors_per_s": resource.networks[1].rx_errors_per_s,
+                        "tx_errors_per_s": resource.networks[1].tx_errors_per_s,
+                        "rx_dropped_per_s": resource.networks[1].rx_dropped_per_s,
+                        "tx_dropped_per_s": resource.networks[1].tx_dropped_per_s
                     }
-                ]
+                ],
+                "sensors": {
+                    "temperatures": [
+                        {
+                            "label": resource.sensors.temperatures[0].label,
+                            "temperature_celsius": resource.sensors.temperatures[0].temperature_celsius,
+                            "max_celsius": resource.sensors.temperatures[0].max_celsius,
+                            "critical_celsius": resource.sensors.temperatures[0].critical_celsius
+                        }
+                    ],
+                    "fans": [
+                        {
+                            "label": resource.sensors.fans[0].label,
+                            "speed_rpm": resource.sensors.fans[0].speed_rpm
+                        }
+                    ]
+                },
+                "system": {
+                    "load_average_1m": resource.system.load_average_1m,
+                    "load_average_5m": resource.system.load_average_5m,
+                    "load_average_15m": resource.system.load_average_15m,
+                    "uptime_s": resource.system.uptime_s,
+                    "boot_time_s": resource.system.boot_time_s,
+                    "os_name": resource.system.os_name,
+                    "os_version": resource.system.os_version,
+                    "kernel_version": resource.system.kernel_version,
+                    "architecture": resource.system.architecture
+                }
         });
 
         assert_json_eq!(expected_resource, resource);
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+// NOTE: fixed-rate schedule, ticks are planned against a monotonic clock as
+//       multiples of the period from the first tick, so time spent sampling
+//       and publishing does not accumulate into drift
+#[derive(Debug)]
+pub struct Schedule {
+    period: Duration,
+    next_tick: Instant,
+}
+
+impl Schedule {
+    // NOTE: 'wall_clock' is the time since UNIX epoch at 'now', and is used
+    //       to delay the first tick to the next multiple of the period, i.e.
+    //       a 5s period ticks at :00, :05, :10, ... of every minute
+    pub fn new(period: Duration, now: Instant, wall_clock: Duration, align: bool) -> Schedule {
+        let offset = match period.as_nanos() {
+            0 => 0,
+            period_ns if align => match wall_clock.as_nanos() % period_ns {
+                0 => 0,
+                remainder => period_ns - remainder,
+            },
+            _ => 0,
+        };
+
+        Schedule {
+            period,
+            next_tick: now + Duration::from_nanos(offset as u64),
+        }
+    }
+
+    pub fn next_tick(&self) -> Instant {
+        self.next_tick
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_tick <= now
+    }
+
+    // NOTE: moves the schedule to the first tick after 'now', and returns how
+    //       many ticks were skipped because 'now' is already past them
+    pub fn advance(&mut self, now: Instant) -> u64 {
+        let period_ns = self.period.as_nanos().max(1);
+        let missed = (now.saturating_duration_since(self.next_tick).as_nanos() / period_ns) as u64;
+
+        self.next_tick += Duration::from_nanos(((missed as u128 + 1) * period_ns) as u64);
+
+        missed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_rate() {
+        let start = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(1), start, Duration::ZERO, false);
+
+        assert!(schedule.is_due(start));
+        assert_eq!(schedule.advance(start + Duration::from_millis(300)), 0);
+        assert_eq!(schedule.next_tick(), start + Duration::from_secs(1));
+
+        assert!(!schedule.is_due(start + Duration::from_millis(999)));
+        assert!(schedule.is_due(start + Duration::from_millis(1200)));
+        assert_eq!(schedule.advance(start + Duration::from_millis(1200)), 0);
+        assert_eq!(schedule.next_tick(), start + Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_missed_ticks() {
+        let start = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(1), start, Duration::ZERO, false);
+
+        assert_eq!(schedule.advance(start), 0);
+        assert_eq!(schedule.advance(start + Duration::from_millis(3500)), 2);
+        assert_eq!(schedule.next_tick(), start + Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_wall_clock_alignment() {
+        let start = Instant::now();
+
+        let schedule = Schedule::new(
+            Duration::from_secs(5),
+            start,
+            Duration::from_millis(1_700_000_003_250),
+            true,
+        );
+        assert_eq!(schedule.next_tick(), start + Duration::from_millis(1750));
+
+        let schedule = Schedule::new(
+            Duration::from_secs(5),
+            start,
+            Duration::from_secs(1_700_000_000),
+            true,
+        );
+        assert_eq!(schedule.next_tick(), start);
+
+        let schedule = Schedule::new(
+            Duration::from_secs(5),
+            start,
+            Duration::from_millis(1_700_000_003_250),
+            false,
+        );
+        assert_eq!(schedule.next_tick(), start);
+    }
+}
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+// NOTE: states of the TCP connections as numbered in 'include/net/tcp_states.h',
+//       'LISTEN' is left out, as listening sockets are counted separately
+const TCP_STATES: [(u8, &str); 11] = [
+    (0x01, "established"),
+    (0x02, "syn_sent"),
+    (0x03, "syn_recv"),
+    (0x04, "fin_wait1"),
+    (0x05, "fin_wait2"),
+    (0x06, "time_wait"),
+    (0x07, "close"),
+    (0x08, "close_wait"),
+    (0x09, "last_ack"),
+    (0x0B, "closing"),
+    (0x0C, "new_syn_recv"),
+];
+const TCP_LISTEN: u8 = 0x0A;
+
+// NOTE: connection counts are taken from the socket tables of IPv4 and IPv6,
+//       the remaining values are counters of the kernel since boot
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct TcpResource {
+    pub states: BTreeMap<String, u64>,
+    pub listening: u64,
+    pub active_opens: u64,
+    pub passive_opens: u64,
+    pub attempt_fails: u64,
+    pub established_resets: u64,
+    pub in_segments: u64,
+    pub out_segments: u64,
+    pub retransmitted_segments: u64,
+    pub in_errors: u64,
+    pub out_resets: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct UdpResource {
+    pub sockets: u64,
+    pub in_datagrams: u64,
+    pub out_datagrams: u64,
+    pub in_errors: u64,
+    pub no_ports: u64,
+    pub receive_buffer_errors: u64,
+    pub send_buffer_errors: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct SocketsResource {
+    pub tcp: TcpResource,
+    pub udp: UdpResource,
+}
+
+// NOTE: '/proc/net/snmp' has two lines per protocol, the first one with the
+//       names of the counters and the second one with their values, values
+//       that are not counters, like 'MaxConn' of '-1', are skipped
+pub fn parse_snmp(content: &str) -> HashMap<String, HashMap<String, u64>> {
+    let mut protocols: HashMap<String, HashMap<String, u64>> = HashMap::new();
+    let mut lines = content.lines();
+    while let (Some(names), Some(values)) = (lines.next(), lines.next()) {
+        let (Some((protocol, names)), Some((_, values))) =
+            (names.split_once(':'), values.split_once(':'))
+        else {
+            continue;
+        };
+
+        protocols.entry(protocol.to_owned()).or_default().extend(
+            names
+                .split_whitespace()
+                .zip(values.split_whitespace())
+                .filter_map(|(name, value)| Some((name.to_owned(), value.parse().ok()?))),
+        );
+    }
+
+    protocols
+}
+
+// NOTE: counts the sockets of a '/proc/net/{tcp,tcp6,udp,udp6}' table by their
+//       state, the state is the fourth column in hexadecimal, after the slot
+//       number and the local and remote addresses
+pub fn count_socket_states(content: &str) -> HashMap<u8, u64> {
+    let mut states = HashMap::new();
+    for state in content
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(3))
+        .filter_map(|state| u8::from_str_radix(state, 16).ok())
+    {
+        *states.entry(state).or_insert(0) += 1;
+    }
+
+    states
+}
+
+fn read_socket_states(directory: &Path, tables: &[&str]) -> Option<HashMap<u8, u64>> {
+    let mut states: Option<HashMap<u8, u64>> = None;
+    for content in tables
+        .iter()
+        .filter_map(|table| std::fs::read_to_string(directory.join(table)).ok())
+    {
+        let total = states.get_or_insert_with(HashMap::new);
+        for (state, count) in count_socket_states(&content) {
+            *total.entry(state).or_insert(0) += count;
+        }
+    }
+
+    states
+}
+
+// NOTE: 'None' when neither the counters nor the socket tables can be read,
+//       missing IPv6 tables are expected on kernels without IPv6 support
+fn read_sockets_from(directory: &Path) -> Option<SocketsResource> {
+    let snmp = std::fs::read_to_string(directory.join("snmp")).ok();
+    let tcp_states = read_socket_states(directory, &["tcp", "tcp6"]);
+    let udp_states = read_socket_states(directory, &["udp", "udp6"]);
+    if snmp.is_none() && tcp_states.is_none() && udp_states.is_none() {
+        return None;
+    }
+
+    let snmp = snmp.map(|content| parse_snmp(&content)).unwrap_or_default();
+    let counter = |protocol: &str, name: &str| {
+        snmp.get(protocol)
+            .and_then(|counters| counters.get(name))
+            .copied()
+            .unwrap_or(0)
+    };
+    let tcp_states = tcp_states.unwrap_or_default();
+
+    Some(SocketsResource {
+        tcp: TcpResource {
+            states: TCP_STATES
+                .iter()
+                .map(|(state, name)| {
+                    (
+                        name.to_string(),
+                        tcp_states.get(state).copied().unwrap_or(0),
+                    )
+                })
+                .collect(),
+            listening: tcp_states.get(&TCP_LISTEN).copied().unwrap_or(0),
+            active_opens: counter("Tcp", "ActiveOpens"),
+            passive_opens: counter("Tcp", "PassiveOpens"),
+            attempt_fails: counter("Tcp", "AttemptFails"),
+            established_resets: counter("Tcp", "EstabResets"),
+            in_segments: counter("Tcp", "InSegs"),
+            out_segments: counter("Tcp", "OutSegs"),
+            retransmitted_segments: counter("Tcp", "RetransSegs"),
+            in_errors: counter("Tcp", "InErrs"),
+            out_resets: counter("Tcp", "OutRsts"),
+        },
+        udp: UdpResource {
+            sockets: udp_states.unwrap_or_default().values().sum(),
+            in_datagrams: counter("Udp", "InDatagrams"),
+            out_datagrams: counter("Udp", "OutDatagrams"),
+            in_errors: counter("Udp", "InErrors"),
+            no_ports: counter("Udp", "NoPorts"),
+            receive_buffer_errors: counter("Udp", "RcvbufErrors"),
+            send_buffer_errors: counter("Udp", "SndbufErrors"),
+        },
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_sockets() -> Option<SocketsResource> {
+    read_sockets_from(Path::new("/proc/net"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_sockets() -> Option<SocketsResource> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/proc/net");
+
+    #[test]
+    fn test_parse_snmp() {
+        let snmp = parse_snmp(include_str!("../tests/fixtures/proc/net/snmp"));
+
+        assert_eq!(snmp["Tcp"]["RetransSegs"], 1841);
+        assert_eq!(snmp["Tcp"]["CurrEstab"], 4);
+        assert!(!snmp["Tcp"].contains_key("MaxConn"));
+        assert_eq!(snmp["Udp"]["InDatagrams"], 52218);
+        assert!(parse_snmp("").is_empty());
+        assert!(parse_snmp("Tcp: ActiveOpens\n").is_empty());
+    }
+
+    #[test]
+    fn test_count_socket_states() {
+        let states = count_socket_states(include_str!("../tests/fixtures/proc/net/tcp"));
+
+        assert_eq!(
+            states,
+            HashMap::from([(0x01, 3), (0x03, 2), (0x06, 1), (0x0A, 2)])
+        );
+        assert!(count_socket_states("  sl  local_address rem_address   st\n").is_empty());
+    }
+
+    #[test]
+    fn test_read_sockets() {
+        let sockets = read_sockets_from(Path::new(FIXTURES)).expect("socket statistics");
+
+        assert_eq!(sockets.tcp.states.len(), TCP_STATES.len());
+        assert_eq!(sockets.tcp.states["established"], 4);
+        assert_eq!(sockets.tcp.states["syn_recv"], 2);
+        assert_eq!(sockets.tcp.states["time_wait"], 1);
+        assert_eq!(sockets.tcp.states["close_wait"], 0);
+        assert_eq!(sockets.tcp.listening, 3);
+        assert_eq!(sockets.tcp.active_opens, 10422);
+        assert_eq!(sockets.tcp.retransmitted_segments, 1841);
+        assert_eq!(sockets.tcp.in_errors, 3);
+        assert_eq!(
+            sockets.udp,
+            UdpResource {
+                sockets: 3,
+                in_datagrams: 52218,
+                out_datagrams: 52560,
+                in_errors: 12,
+                no_ports: 341,
+                receive_buffer_errors: 12,
+                send_buffer_errors: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_missing_sockets() {
+        assert_eq!(
+            read_sockets_from(&Path::new(FIXTURES).join("missing")),
+            None
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+const PROPERTIES: &str = "Id,LoadState,ActiveState,SubState,NRestarts,MainPID";
+
+// NOTE: 'restarts' is reported only for services, and 'main_pid' only while
+//       the unit has a running main process
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct SystemdUnitResource {
+    pub unit: String,
+    pub load_state: String,
+    pub active_state: String,
+    pub sub_state: String,
+    pub restarts: Option<u64>,
+    pub main_pid: Option<u32>,
+}
+
+impl SystemdUnitResource {
+    pub fn unknown(unit: &str) -> SystemdUnitResource {
+        SystemdUnitResource {
+            unit: unit.to_owned(),
+            load_state: "unknown".to_owned(),
+            active_state: "unknown".to_owned(),
+            sub_state: "unknown".to_owned(),
+            restarts: None,
+            main_pid: None,
+        }
+    }
+}
+
+// NOTE: 'systemctl show' prints the properties of every unit as 'key=value'
+//       lines, separated from the next unit with an empty line, in the order
+//       in which the units were passed to it
+fn parse_blocks(content: &str) -> Vec<HashMap<&str, &str>> {
+    content
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect::<HashMap<&str, &str>>()
+        })
+        .filter(|properties| !properties.is_empty())
+        .collect()
+}
+
+// NOTE: the units are reported under the names they were configured with, and
+//       units missing from the output are reported with an 'unknown' state
+pub fn parse_systemctl_show(content: &str, units: &[String]) -> Vec<SystemdUnitResource> {
+    let blocks = parse_blocks(content);
+
+    units
+        .iter()
+        .enumerate()
+        .map(|(i, unit)| match blocks.get(i) {
+            Some(properties) => {
+                let property = |name: &str| {
+                    properties
+                        .get(name)
+                        .map(|value| value.to_string())
+                        .unwrap_or("unknown".to_owned())
+                };
+                SystemdUnitResource {
+                    unit: unit.to_owned(),
+                    load_state: property("LoadState"),
+                    active_state: property("ActiveState"),
+                    sub_state: property("SubState"),
+                    restarts: properties
+                        .get("NRestarts")
+                        .and_then(|value| value.parse().ok()),
+                    main_pid: properties
+                        .get("MainPID")
+                        .and_then(|value| value.parse().ok())
+                        .filter(|pid| *pid != 0),
+                }
+            }
+            None => SystemdUnitResource::unknown(unit),
+        })
+        .collect()
+}
+
+// NOTE: unit names are made of ASCII letters, digits and ':', '-', '_', '.',
+//       '\' and '@', and are at most 255 characters long, 'systemctl' appends
+//       '.service' to names without a suffix
+pub fn is_valid_unit_name(unit: &str) -> bool {
+    !unit.is_empty()
+        && unit.len() <= 255
+        && unit
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+}
+
+// NOTE: 'None' when 'systemctl' can't be run, 'Some(None)' when it failed
+fn run_systemctl_show(units: &[String]) -> Option<Option<String>> {
+    let output = Command::new("systemctl")
+        .arg("show")
+        .arg(format!("--property={}", PROPERTIES))
+        .arg("--")
+        .args(units)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    Some(match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => None,
+    })
+}
+
+// NOTE: all units are read with a single 'systemctl' call, as long as it
+//       succeeds, otherwise every unit is read on its own, so a unit that
+//       makes 'systemctl' fail doesn't hide the state of the others
+pub fn read_systemd_units(units: &[String]) -> Vec<SystemdUnitResource> {
+    if units.is_empty() {
+        return Vec::new();
+    }
+
+    match run_systemctl_show(units) {
+        Some(Some(output)) => parse_systemctl_show(&output, units),
+        Some(None) if units.len() > 1 => units
+            .chunks(1)
+            .flat_map(|unit| match run_systemctl_show(unit) {
+                Some(Some(output)) => parse_systemctl_show(&output, unit),
+                _ => vec![SystemdUnitResource::unknown(&unit[0])],
+            })
+            .collect(),
+        _ => units
+            .iter()
+            .map(|unit| SystemdUnitResource::unknown(unit))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOW: &str = include_str!("../tests/fixtures/systemctl/show");
+
+    fn units(units: &[&str]) -> Vec<String> {
+        units.iter().map(|unit| unit.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_systemctl_show() {
+        let units = parse_systemctl_show(
+            SHOW,
+            &units(&["nginx", "tum.service", "missing", "backup.timer"]),
+        );
+
+        assert_eq!(
+            units,
+            vec![
+                SystemdUnitResource {
+                    unit: "nginx".to_owned(),
+                    load_state: "loaded".to_owned(),
+                    active_state: "active".to_owned(),
+                    sub_state: "running".to_owned(),
+                    restarts: Some(0),
+                    main_pid: Some(812),
+                },
+                SystemdUnitResource {
+                    unit: "tum.service".to_owned(),
+                    load_state: "loaded".to_owned(),
+                    active_state: "failed".to_owned(),
+                    sub_state: "failed".to_owned(),
+                    restarts: Some(3),
+                    main_pid: None,
+                },
+                SystemdUnitResource {
+                    unit: "missing".to_owned(),
+                    load_state: "not-found".to_owned(),
+                    active_state: "inactive".to_owned(),
+                    sub_state: "dead".to_owned(),
+                    restarts: Some(0),
+                    main_pid: None,
+                },
+                SystemdUnitResource {
+                    unit: "backup.timer".to_owned(),
+                    load_state: "loaded".to_owned(),
+                    active_state: "active".to_owned(),
+                    sub_state: "waiting".to_owned(),
+                    restarts: None,
+                    main_pid: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_incomplete_systemctl_show() {
+        assert_eq!(
+            parse_systemctl_show(
+                "Id=nginx.service\nActiveState=active\n",
+                &units(&["nginx", "sshd"])
+            ),
+            vec![
+                SystemdUnitResource {
+                    unit: "nginx".to_owned(),
+                    load_state: "unknown".to_owned(),
+                    active_state: "active".to_owned(),
+                    sub_state: "unknown".to_owned(),
+                    restarts: None,
+                    main_pid: None,
+                },
+                SystemdUnitResource::unknown("sshd"),
+            ]
+        );
+        assert!(parse_systemctl_show(SHOW, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_unit_name_validation() {
+        assert!(is_valid_unit_name("nginx"));
+        assert!(is_valid_unit_name("getty@tty1.service"));
+        assert!(is_valid_unit_name("dev-disk-by\\x2dlabel-root.device"));
+        assert!(!is_valid_unit_name(""));
+        assert!(!is_valid_unit_name("my unit.service"));
+        assert!(!is_valid_unit_name("nginx/.service"));
+        assert!(!is_valid_unit_name(&"a".repeat(256)));
+    }
+}